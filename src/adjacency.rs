@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+// Face connectivity of a model: the faces around each (undirected) edge and
+// each vertex, by face index.
+#[derive(Clone, Debug, Default)]
+pub struct Adjacency {
+    edges: HashMap<(usize, usize), Vec<usize>>,
+    vertices: Vec<Vec<usize>>,
+}
+
+impl Adjacency {
+    pub fn new<'a>(vertex_count: usize, faces: impl Iterator<Item = &'a [usize]>) -> Adjacency {
+        let mut adjacency = Adjacency {
+            edges: HashMap::new(),
+            vertices: vec![Vec::new(); vertex_count],
+        };
+
+        for (face, indices) in faces.enumerate() {
+            for (i, &a) in indices.iter().enumerate() {
+                let b = indices[(i + 1) % indices.len()];
+                adjacency.edges.entry(edge_key(a, b)).or_default().push(face);
+                adjacency.vertices[a].push(face);
+            }
+        }
+        adjacency
+    }
+
+    pub fn edge_faces(&self, a: usize, b: usize) -> &[usize] {
+        self.edges.get(&edge_key(a, b)).map_or(&[], |faces| faces.as_slice())
+    }
+
+    pub fn vertex_faces(&self, vertex: usize) -> &[usize] {
+        self.vertices.get(vertex).map_or(&[], |faces| faces.as_slice())
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = ((usize, usize), &[usize])> {
+        self.edges.iter().map(|(&edge, faces)| (edge, faces.as_slice()))
+    }
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
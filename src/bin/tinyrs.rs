@@ -1,5 +1,9 @@
 use std::error::Error;
-use std::path::Path;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::time::Instant;
 
 use clap::Parser;
 
@@ -8,63 +12,272 @@ use sdl2::pixels::Color;
 use sdl2::event::Event;
 use tinyrs::canvas::CanvasBuilder;
 use tinyrs::common::Resolution;
-use tinyrs::geometry::{Mat4x4f, Vec3f};
-use tinyrs::renderer::Renderer;
+use tinyrs::errors::RenderError;
+use tinyrs::framebuffer::{FrameBuffer, Rgb};
+use tinyrs::geometry::Mat4x4f;
+use tinyrs::headless::view_port;
+use tinyrs::renderer::{DepthFunc, MissingNormalPolicy, Renderer, ShadingMode};
 use tinyrs::model::Model;
+use tinyrs::scene::PerspectiveCamera;
+use tinyrs::zbuffer::ZBuffer;
+
+// Camera distance factor per mouse wheel step.
+const ZOOM_STEP: f64 = 1.1;
+// Camera orbit in radians per pixel of mouse drag.
+const ORBIT_STEP: f64 = 0.01;
+// Color of the model's `l` polylines.
+const LINE_COLOR: Rgb = Rgb { r: 255, g: 255, b: 0 };
+
+// Camera distance for models scaled by --fit to span [-0.5, 0.5], at which
+// they fill most of the view.
+const FIT_DISTANCE: f64 = 1.5;
 
 #[derive(Parser, Debug)]
 struct Args {
+    /// OBJ file to load, optionally gzip compressed.
     #[arg(long)]
     file: std::path::PathBuf,
 
+    /// Window or frame width in pixels.
     #[arg(long, default_value_t = 1024)]
     width: u32,
 
+    /// Window or frame height in pixels.
     #[arg(long, default_value_t = 768)]
     height: u32,
+
+    /// Remove degenerate faces after loading.
+    #[arg(long)]
+    clean: bool,
+
+    /// Center the model and scale it to fit the view, generating normals if
+    /// it has none. Recommended when viewing a model for the first time.
+    #[arg(long)]
+    fit: bool,
+
+    /// Show the frame rate and face count in the viewer.
+    #[arg(long)]
+    stats: bool,
+
+    /// Print the time spent in each render stage once a second.
+    #[arg(long)]
+    profile: bool,
+
+    /// Rotate the model so that its principal axes line up with x, y and z.
+    #[arg(long)]
+    auto_orient: bool,
+
+    /// Show the convex hull of the model instead of the model itself.
+    #[arg(long)]
+    hull: bool,
+
+    /// Draw about this many triangles per frame, continuing where the last
+    /// frame stopped. Polygon faces are drawn whole.
+    #[arg(long)]
+    triangle_budget: Option<usize>,
+
+    /// Render this many frames of a turntable to --output as PPM files
+    /// instead of opening a window.
+    #[arg(long)]
+    dump_frames: Option<u32>,
+
+    /// Directory the frames of --dump-frames are written to.
+    #[arg(long, default_value = "frames")]
+    output: PathBuf,
+
+    /// Shading mode: gouraud, face-id or normal-rgb. Tab cycles through them
+    /// in the viewer.
+    #[arg(long, value_parser = shading_mode, default_value = "gouraud")]
+    shading: ShadingMode,
+
+    /// Color faces without normals red, green and blue instead of lighting
+    /// them with their face normal.
+    #[arg(long)]
+    rainbow_missing_normals: bool,
+}
+
+fn shading_mode(name: &str) -> Result<ShadingMode, String> {
+    match name {
+        "gouraud" => Ok(ShadingMode::Gouraud),
+        "face-id" => Ok(ShadingMode::FaceId),
+        "normal-rgb" => Ok(ShadingMode::NormalRgb),
+        _ => Err(format!("Unknown shading mode {}, expected gouraud, face-id or normal-rgb", name)),
+    }
+}
+
+fn title(shading_mode: ShadingMode) -> String {
+    format!("TinyRS - {:?}", shading_mode)
+}
+
+fn aspect(resolution: Resolution) -> f64 {
+    resolution.width as f64 / resolution.height as f64
 }
 
-fn app<P: AsRef<Path>>(filename: P, resolution: Resolution) -> Result<(), Box<dyn Error>> {
+fn radius(model: &Model) -> f64 {
+    model.vertices().iter().map(|v| v.norm()).fold(0.0, f64::max)
+}
+
+fn camera(args: &Args, model: &Model) -> PerspectiveCamera {
+    let mut camera = PerspectiveCamera::new(if args.fit { FIT_DISTANCE } else { 3.0 });
+    camera.clamp_distance(radius(model));
+    camera
+}
+
+// Closest camera distance (in mouse wheel steps) at which the whole model is
+// still on screen.
+fn frame_model(renderer: &Renderer,
+               model: &Model,
+               resolution: Resolution,
+               view_port: Mat4x4f,
+               orientation: Mat4x4f,
+               camera: &mut PerspectiveCamera) {
+    let radius = radius(model);
+    camera.distance = 0.0;
+    camera.clamp_distance(radius);
+    while camera.distance < camera.far {
+        let fits = renderer.screen_bounds(model, view_port, camera.projection(aspect(resolution)) * orientation)
+            .is_some_and(|(min, max)| min.x >= 0 && min.y >= 0 &&
+                max.x < resolution.width as i32 && max.y < resolution.height as i32);
+        if fits {
+            break;
+        }
+        camera.distance *= ZOOM_STEP;
+    }
+}
+
+fn load_model(args: &Args) -> Result<Model, Box<dyn Error>> {
+    let mut model = Model::from_file(&args.file)?;
+    if args.clean {
+        let removed = model.remove_degenerate_faces();
+        println!("Removed {} degenerate faces", removed);
+    }
+    if args.hull {
+        model = model.convex_hull();
+    }
+    if args.fit {
+        model.normalize_to_unit_cube();
+        if model.stats().normals == 0 {
+            model.compute_normals();
+        }
+    }
+    if model.is_empty() && model.lines().is_empty() {
+        return Err(RenderError::EmptyModel(args.file.display().to_string()).into());
+    }
+    Ok(model)
+}
+
+fn orientation(args: &Args, model: &Model) -> Mat4x4f {
+    if args.auto_orient {
+        Mat4x4f::from(model.principal_axes())
+    } else {
+        Mat4x4f::identity()
+    }
+}
+
+// Renders a full turntable of the model without opening a window, writing one
+// PPM per frame into the output directory.
+fn dump_frames(args: &Args, frames: u32) -> Result<(), Box<dyn Error>> {
+    let resolution = Resolution::new((args.width, args.height));
+    let model = load_model(args)?;
+    let orientation = orientation(args, &model);
+
+    let mut renderer = Renderer::new(resolution);
+    renderer.set_depth_func(DepthFunc::Less);
+    renderer.set_shading_mode(args.shading);
+    if args.rainbow_missing_normals {
+        renderer.set_missing_normal_policy(MissingNormalPolicy::Rainbow);
+    }
+    let mut framebuffer = FrameBuffer::new(resolution);
+    let mut zbuffer = ZBuffer::new(resolution, renderer.depth_clear_value());
+
+    let view_port = view_port(resolution);
+    let camera = camera(args, &model);
+    let projection = camera.projection(aspect(resolution));
+
+    std::fs::create_dir_all(&args.output)?;
+    for frame in 0..frames {
+        framebuffer.clear(Rgb::default(), &mut zbuffer);
+
+        let turntable = Mat4x4f::rotation_y(2.0 * PI * frame as f64 / frames as f64);
+        let light_direction = camera.headlight(turntable * orientation);
+        renderer.render_model(&mut framebuffer, &mut zbuffer, &light_direction,
+                              &model, view_port, projection * turntable * orientation)?;
+        renderer.render_lines(&mut framebuffer, &model, view_port,
+                              projection * turntable * orientation, LINE_COLOR)?;
+
+        let path = args.output.join(format!("frame_{:04}.ppm", frame));
+        framebuffer.write_ppm(&mut BufWriter::new(File::create(path)?))?;
+    }
+
+    Ok(())
+}
+
+fn app(args: Args) -> Result<(), Box<dyn Error>> {
+    if let Some(frames) = args.dump_frames {
+        return dump_frames(&args, frames);
+    }
+
+    let resolution = Resolution::new((args.width, args.height));
+
     let sdl_context = sdl2::init()?;
     let mut canvas = CanvasBuilder::new(&sdl_context)
         .resolution(resolution)
-        .title("TinyRS")
+        .title(&title(args.shading))
         .build()?;
 
-    let model = Model::from_file(filename)?;
+    let model = load_model(&args)?;
+    let orientation = orientation(&args, &model);
 
-    let renderer = Renderer::new(resolution);
-    let mut zbuffer = vec![f64::MIN; (resolution.width * resolution.height) as usize];
+    let mut renderer = Renderer::new(resolution);
+    renderer.set_profiling(args.profile);
+    renderer.set_triangle_budget(args.triangle_budget);
+    renderer.set_depth_func(DepthFunc::Less);
+    renderer.set_shading_mode(args.shading);
+    if args.rainbow_missing_normals {
+        renderer.set_missing_normal_policy(MissingNormalPolicy::Rainbow);
+    }
+    let mut zbuffer = ZBuffer::new(resolution, renderer.depth_clear_value());
 
-    let light_direction = Vec3f::new(0.0, 0.0, 1.0);
+    let mut camera = camera(&args, &model);
+    let radius = radius(&model);
 
-    let mut camera = Vec3f::new(0.0,0.0,3.0);
+    let view_port = view_port(resolution);
 
-    let view_port = Mat4x4f::viewport(
-        resolution.width as f64 / 8.0,
-        resolution.height as f64 / 8.0,
-        resolution.width as f64 * 3.0 / 4.0,
-        resolution.height as f64 * 3.0 / 4.0
-    );
+    let mut profile_timer = Instant::now();
+
+    let mut fps = 0;
+    let mut frames = 0;
+    let mut fps_timer = Instant::now();
+
+    let mut screenshot = false;
 
     let mut event_pump = sdl_context.event_pump()?;
     'running: loop {
         canvas.set_draw_color(Color::RGB(0, 0, 0));
         canvas.clear();
 
-        let projection = Mat4x4f::from([
-            1.0, 0.0,  0.0,            0.0,
-            0.0, 1.0,  0.0,            0.0,
-            0.0, 0.0,  1.0,            0.0,
-            0.0, 0.0, -1.0 / camera.z, 1.0,
-        ]);
+        let projection = camera.projection(aspect(resolution));
+        let light_direction = camera.headlight(orientation);
+
+        renderer.render_model(&mut canvas, &mut zbuffer, &light_direction,
+                              &model, view_port, projection * orientation)?;
+        renderer.render_lines(&mut canvas, &model, view_port, projection * orientation, LINE_COLOR)?;
+
+        if args.stats {
+            frames += 1;
+            if fps_timer.elapsed().as_secs_f64() >= 1.0 {
+                fps = frames;
+                frames = 0;
+                fps_timer = Instant::now();
+            }
 
-        for face in model.iter() {
-            renderer.render_face(&mut canvas, &mut zbuffer, &light_direction,
-                                 face, view_port, projection)?;
+            let white = Rgb::new(255, 255, 255);
+            renderer.draw_text(&mut canvas, 8, 8, &format!("FPS: {}", fps), white)?;
+            renderer.draw_text(&mut canvas, 8, 18,
+                               &format!("FACES: {}", model.stats().faces), white)?;
         }
 
-        zbuffer.fill(f64::MIN);
+        zbuffer.clear();
 
         for event in event_pump.poll_iter() {
             match event {
@@ -72,15 +285,43 @@ fn app<P: AsRef<Path>>(filename: P, resolution: Resolution) -> Result<(), Box<dy
                 Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                     break 'running
                 },
+                Event::KeyDown { keycode: Some(Keycode::F12), .. } => {
+                    screenshot = true;
+                }
+                Event::KeyDown { keycode: Some(Keycode::Tab), .. } => {
+                    renderer.set_shading_mode(renderer.shading_mode().next());
+                    canvas.window_mut().set_title(&title(renderer.shading_mode()))?;
+                }
+                Event::KeyDown { keycode: Some(Keycode::F), .. } => {
+                    frame_model(&renderer, &model, resolution, view_port, orientation, &mut camera);
+                }
+                Event::MouseMotion { mousestate, xrel, yrel, .. } if mousestate.left() => {
+                    camera.orbit(-xrel as f64 * ORBIT_STEP, yrel as f64 * ORBIT_STEP);
+                }
                 Event::MouseWheel { y, .. } => {
-                    camera.z += 0.25 * y.signum() as f64;
-                    camera.z = f64::clamp(camera.z, 2.0, 5.0);
+                    camera.distance *= ZOOM_STEP.powi(y.signum());
+                    camera.clamp_distance(radius);
                 }
                 _ => {}
             }
         }
 
+        if screenshot {
+            let framebuffer = renderer.read_pixels(&canvas)?;
+            framebuffer.write_ppm(&mut BufWriter::new(File::create("screenshot.ppm")?))?;
+            println!("Saved screenshot.ppm");
+            screenshot = false;
+        }
+
+        let present_start = Instant::now();
         canvas.present();
+
+        if args.profile && profile_timer.elapsed().as_secs_f64() >= 1.0 {
+            let mut timings = renderer.timings();
+            timings.present = present_start.elapsed();
+            println!("{}", timings);
+            profile_timer = Instant::now();
+        }
     }
 
     Ok(())
@@ -88,7 +329,7 @@ fn app<P: AsRef<Path>>(filename: P, resolution: Resolution) -> Result<(), Box<dy
 
 pub fn main() {
     let args = Args::parse();
-    app(args.file, (args.width, args.height).into())
+    app(args)
         .map_err(|e| eprintln!("{}", e))
         .ok();
 }
\ No newline at end of file
@@ -0,0 +1,121 @@
+use crate::geometry::{Aabb, Triangle, Vec3f};
+use crate::model::Model;
+
+// Triangles per leaf below which nodes aren't split any further.
+const LEAF_SIZE: usize = 4;
+
+enum BvhNode {
+    Leaf { bounds: Aabb, start: usize, end: usize },
+    Interior { bounds: Aabb, left: usize, right: usize },
+}
+
+impl BvhNode {
+    fn bounds(&self) -> &Aabb {
+        match self {
+            BvhNode::Leaf { bounds, .. } | BvhNode::Interior { bounds, .. } => bounds,
+        }
+    }
+}
+
+// Bounding volume hierarchy over the triangles of a model, split at the
+// median triangle along the longest axis of their bounding box centers.
+// Triangles are identified by their position in `Model::triangles()`.
+pub struct Bvh {
+    triangles: Vec<Triangle>,
+    // triangle indices, grouped so that every leaf owns a contiguous range
+    order: Vec<usize>,
+    nodes: Vec<BvhNode>,
+}
+
+impl Bvh {
+    pub fn new(model: &Model) -> Self {
+        Bvh::from_triangles(model.triangles().collect())
+    }
+
+    pub fn from_triangles(triangles: Vec<Triangle>) -> Self {
+        let bounds: Vec<Aabb> = triangles.iter()
+            .map(|triangle| Aabb::from_points(&triangle.vertices()))
+            .collect();
+        let mut bvh = Bvh { order: (0..triangles.len()).collect(), triangles, nodes: Vec::new() };
+        if !bvh.triangles.is_empty() {
+            bvh.build(&bounds, 0, bvh.triangles.len());
+        }
+        bvh
+    }
+
+    pub fn len(&self) -> usize {
+        self.triangles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    // Nearest hit along `origin + t * dir` with t >= 0, as the ray parameter
+    // and the index of the triangle hit.
+    pub fn intersect_ray(&self, origin: Vec3f, dir: Vec3f) -> Option<(f64, usize)> {
+        let mut nearest: Option<(f64, usize)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            match node.bounds().intersect_ray(origin, dir) {
+                Some(t) if nearest.is_none_or(|(nearest, _)| t <= nearest) => {}
+                _ => continue,
+            }
+            match *node {
+                BvhNode::Leaf { start, end, .. } => {
+                    for &index in &self.order[start..end] {
+                        if let Some(t) = self.triangles[index].intersect_ray(origin, dir) {
+                            if nearest.is_none_or(|(nearest, _)| t < nearest) {
+                                nearest = Some((t, index));
+                            }
+                        }
+                    }
+                }
+                BvhNode::Interior { left, right, .. } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+        nearest
+    }
+
+    fn build(&mut self, bounds: &[Aabb], start: usize, end: usize) -> usize {
+        let node_bounds = self.order[start..end].iter()
+            .fold(Aabb::empty(), |node_bounds, &index| node_bounds.union(bounds[index]));
+        let node = self.nodes.len();
+
+        if end - start <= LEAF_SIZE {
+            self.nodes.push(BvhNode::Leaf { bounds: node_bounds, start, end });
+            return node;
+        }
+
+        let centers = self.order[start..end].iter()
+            .fold(Aabb::empty(), |centers, &index| centers.extend(bounds[index].center()));
+        let size = centers.size();
+        let axis = |p: Vec3f| if size.x >= size.y && size.x >= size.z {
+            p.x
+        } else if size.y >= size.z {
+            p.y
+        } else {
+            p.z
+        };
+
+        let mid = (start + end) / 2;
+        self.order[start..end].select_nth_unstable_by(mid - start, |&a, &b| {
+            axis(bounds[a].center()).total_cmp(&axis(bounds[b].center()))
+        });
+
+        // placeholder until both children are built
+        self.nodes.push(BvhNode::Leaf { bounds: node_bounds, start, end });
+        let left = self.build(bounds, start, mid);
+        let right = self.build(bounds, mid, end);
+        self.nodes[node] = BvhNode::Interior { bounds: node_bounds, left, right };
+        node
+    }
+}
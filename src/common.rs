@@ -1,4 +1,38 @@
-#[derive(Copy, Clone)]
+// Integer screen position. Converts to and from SDL's point when the `sdl`
+// feature is on.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Self {
+        Point { x, y }
+    }
+}
+
+impl From<(i32, i32)> for Point {
+    fn from(pair: (i32, i32)) -> Self {
+        Point::new(pair.0, pair.1)
+    }
+}
+
+#[cfg(feature = "sdl")]
+impl From<sdl2::rect::Point> for Point {
+    fn from(point: sdl2::rect::Point) -> Self {
+        Point::new(point.x, point.y)
+    }
+}
+
+#[cfg(feature = "sdl")]
+impl From<Point> for sdl2::rect::Point {
+    fn from(point: Point) -> Self {
+        sdl2::rect::Point::new(point.x, point.y)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Resolution {
     pub width: u32,
     pub height: u32,
@@ -4,13 +4,20 @@ use std::fmt;
 pub enum RenderError {
     SDLError(String),
     ContextError(String),
+    #[cfg(feature = "sdl")]
     WindowBuildError(sdl2::video::WindowBuildError),
+    #[cfg(feature = "sdl")]
     IntegerOrSdlError(sdl2::IntegerOrSdlError),
     FileReadError(std::io::Error),
     VertexParsingError(String),
     NormalParsingError(String),
     TextureParsingError(String),
     FaceParsingError(String),
+    LineParsingError(String),
+    SmoothingGroupParsingError(String),
+    MaterialFileError(String),
+    MaterialParsingError(String),
+    EmptyModel(String),
 }
 
 impl fmt::Display for RenderError {
@@ -20,8 +27,10 @@ impl fmt::Display for RenderError {
                 write!(f, "SDL error: {}", msg),
             RenderError::ContextError(msg) =>
                 write!(f, "Unable to create context: {}", msg),
+            #[cfg(feature = "sdl")]
             RenderError::WindowBuildError(err) =>
                 write!(f, "Unable to build window: {}", err.to_string()),
+            #[cfg(feature = "sdl")]
             RenderError::IntegerOrSdlError(err) =>
                 write!(f, "Unable to initialize SDL: {}", err.to_string()),
             RenderError::FileReadError(err) =>
@@ -34,16 +43,28 @@ impl fmt::Display for RenderError {
                 write!(f, "Unable to parse texture: {}", msg),
             RenderError::FaceParsingError(msg) =>
                 write!(f, "Unable to parse face: {}", msg),
+            RenderError::LineParsingError(msg) =>
+                write!(f, "Unable to parse line element: {}", msg),
+            RenderError::SmoothingGroupParsingError(msg) =>
+                write!(f, "Unable to parse smoothing group: {}", msg),
+            RenderError::MaterialFileError(msg) =>
+                write!(f, "Unable to read material file: {}", msg),
+            RenderError::MaterialParsingError(msg) =>
+                write!(f, "Unable to parse material: {}", msg),
+            RenderError::EmptyModel(msg) =>
+                write!(f, "Model has no faces: {}", msg),
         }
     }
 }
 
+#[cfg(feature = "sdl")]
 impl From<sdl2::video::WindowBuildError> for RenderError {
     fn from(err: sdl2::video::WindowBuildError) -> RenderError {
         RenderError::WindowBuildError(err)
     }
 }
 
+#[cfg(feature = "sdl")]
 impl From<sdl2::IntegerOrSdlError> for RenderError {
     fn from(err: sdl2::IntegerOrSdlError) -> RenderError {
         RenderError::IntegerOrSdlError(err)
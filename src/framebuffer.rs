@@ -0,0 +1,145 @@
+use std::io::Write;
+
+use crate::common::Resolution;
+use crate::geometry::Vec3f;
+use crate::zbuffer::ZBuffer;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub fn new(r: u8, g: u8, b: u8) -> Rgb {
+        Rgb { r, g, b }
+    }
+}
+
+impl From<Vec3f> for Rgb {
+    fn from(color: Vec3f) -> Rgb {
+        Rgb::new(f64::clamp(color.x, 0.0, 255.0) as u8,
+                 f64::clamp(color.y, 0.0, 255.0) as u8,
+                 f64::clamp(color.z, 0.0, 255.0) as u8)
+    }
+}
+
+impl From<Rgb> for Vec3f {
+    fn from(rgb: Rgb) -> Vec3f {
+        Vec3f::new(rgb.r as f64, rgb.g as f64, rgb.b as f64)
+    }
+}
+
+#[cfg(feature = "sdl")]
+impl From<Rgb> for sdl2::pixels::Color {
+    fn from(rgb: Rgb) -> sdl2::pixels::Color {
+        sdl2::pixels::Color::RGB(rgb.r, rgb.g, rgb.b)
+    }
+}
+
+pub trait RenderTarget {
+    fn draw_pixel(&mut self, x: u32, y: u32, color: Rgb) -> Result<(), String>;
+}
+
+#[cfg(feature = "sdl")]
+impl<T: sdl2::render::RenderTarget> RenderTarget for sdl2::render::Canvas<T> {
+    fn draw_pixel(&mut self, x: u32, y: u32, color: Rgb) -> Result<(), String> {
+        self.set_draw_color(color);
+        self.draw_point(sdl2::rect::Point::new(x as i32, y as i32))
+    }
+}
+
+pub struct FrameBuffer {
+    resolution: Resolution,
+    pixels: Vec<Rgb>,
+}
+
+impl FrameBuffer {
+    pub fn new<R: Into<Resolution>>(resolution: R) -> Self {
+        let resolution = resolution.into();
+        let pixels = vec![Rgb::default(); (resolution.width * resolution.height) as usize];
+        FrameBuffer { resolution, pixels }
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    pub fn pixels(&self) -> &[Rgb] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Rgb> {
+        if x < self.resolution.width && y < self.resolution.height {
+            Some(self.pixels[(x + self.resolution.width * y) as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn fill(&mut self, color: Rgb) {
+        self.pixels.fill(color);
+    }
+
+    // Mixes `color` over what is already there by `coverage` in [0, 1].
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: Rgb, coverage: f64) {
+        if x < self.resolution.width && y < self.resolution.height {
+            let index = (x + self.resolution.width * y) as usize;
+            let coverage = coverage.clamp(0.0, 1.0);
+            let blended = Vec3f::from(color) * coverage + Vec3f::from(self.pixels[index]) * (1.0 - coverage);
+            self.pixels[index] = Rgb::from(blended);
+        }
+    }
+
+    // Begin-frame clear for the headless path: resets the color buffer and
+    // the depth buffer rendered alongside it to its depth-func sentinel.
+    pub fn clear(&mut self, color: Rgb, zbuffer: &mut ZBuffer) {
+        self.fill(color);
+        zbuffer.clear();
+    }
+
+    // First pixel, in row-major order, where a channel differs by more than
+    // `tolerance`, as (x, y, ours, theirs).
+    pub fn first_difference(&self, other: &FrameBuffer, tolerance: u8) -> Option<(u32, u32, Rgb, Rgb)> {
+        let width = self.resolution.width;
+        self.pixels.iter().zip(&other.pixels)
+            .position(|(a, b)| a.r.abs_diff(b.r) > tolerance ||
+                               a.g.abs_diff(b.g) > tolerance ||
+                               a.b.abs_diff(b.b) > tolerance)
+            .map(|index| {
+                let (x, y) = (index as u32 % width, index as u32 / width);
+                (x, y, self.pixels[index], other.pixels[index])
+            })
+    }
+
+    // Test helper comparing two frames, panicking with the first differing
+    // pixel so rasterization regressions are easy to locate.
+    pub fn assert_eq(&self, other: &FrameBuffer, tolerance: u8) {
+        assert_eq!(self.resolution, other.resolution, "Frame buffer resolutions differ");
+        if let Some((x, y, ours, theirs)) = self.first_difference(other, tolerance) {
+            panic!("Frame buffers differ at ({}, {}): {:?} != {:?}", x, y, ours, theirs);
+        }
+    }
+
+    // Tightly packed RGBA bytes, row by row from the top and fully opaque,
+    // the layout textures and HTML canvas `ImageData` expect.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|p| [p.r, p.g, p.b, 255]).collect()
+    }
+
+    pub fn write_ppm<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.resolution.width, self.resolution.height)?;
+        let bytes: Vec<u8> = self.pixels.iter().flat_map(|p| [p.r, p.g, p.b]).collect();
+        writer.write_all(&bytes)
+    }
+}
+
+impl RenderTarget for FrameBuffer {
+    fn draw_pixel(&mut self, x: u32, y: u32, color: Rgb) -> Result<(), String> {
+        if x < self.resolution.width && y < self.resolution.height {
+            self.pixels[(x + self.resolution.width * y) as usize] = color;
+        }
+        Ok(())
+    }
+}
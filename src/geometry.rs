@@ -1,7 +1,7 @@
 use std::fmt::{Display, Formatter};
-use std::ops::{Add, Index, IndexMut, Mul, Sub};
+use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, Neg, Sub, SubAssign};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 pub struct VecUV2f {
     pub u: f64,
     pub v: f64,
@@ -13,7 +13,7 @@ impl VecUV2f {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 pub struct Vec3f {
     pub x: f64,
     pub y: f64,
@@ -25,12 +25,58 @@ impl Vec3f {
         Vec3f { x, y, z }
     }
 
+    // Spherical coordinates with y up, angles in radians: `azimuth` turns
+    // about +y starting at +z towards +x, `elevation` rises from the xz-plane
+    // towards +y and lies in [-pi/2, pi/2].
+    pub fn from_spherical(radius: f64, azimuth: f64, elevation: f64) -> Vec3f {
+        let (sin_azimuth, cos_azimuth) = azimuth.sin_cos();
+        let (sin_elevation, cos_elevation) = elevation.sin_cos();
+        Vec3f::new(radius * cos_elevation * sin_azimuth,
+                   radius * sin_elevation,
+                   radius * cos_elevation * cos_azimuth)
+    }
+
+    // (radius, azimuth, elevation) as taken by `from_spherical`, with the
+    // azimuth in (-pi, pi]. On the y axis, where the azimuth is undefined, it
+    // is 0; the zero vector gives all zeros.
+    pub fn to_spherical(&self) -> (f64, f64, f64) {
+        let radius = self.norm();
+        if radius < f64::MIN_POSITIVE {
+            return (0.0, 0.0, 0.0);
+        }
+        let elevation = (self.y / radius).clamp(-1.0, 1.0).asin();
+        (radius, self.x.atan2(self.z), elevation)
+    }
+
     pub fn dot(&self, other: &Vec3f) -> f64 {
         self.x * other.x + self.y * other.y + self.z * other.z
     }
 
     pub fn norm(&self) -> f64 {
-        self.dot(self).sqrt()
+        self.length_squared().sqrt()
+    }
+
+    // Cheaper than `norm` when only comparing magnitudes.
+    pub fn length_squared(&self) -> f64 {
+        self.dot(self)
+    }
+
+    pub fn distance(&self, other: &Vec3f) -> f64 {
+        (*self - *other).norm()
+    }
+
+    pub fn distance_squared(&self, other: &Vec3f) -> f64 {
+        (*self - *other).length_squared()
+    }
+
+    // `None` for (near) zero vectors, where `normalize` would give NaNs.
+    pub fn try_normalize(&self) -> Option<Vec3f> {
+        let norm = self.norm();
+        if norm < f64::MIN_POSITIVE {
+            None
+        } else {
+            Some(*self / norm)
+        }
     }
 
     pub fn normalize(&self) -> Vec3f {
@@ -38,6 +84,40 @@ impl Vec3f {
         Vec3f::new(self.x * inv_norm, self.y * inv_norm, self.z * inv_norm)
     }
 
+    /// Linear interpolation, `self` at `t = 0` and `other` at `t = 1`. `t` is
+    /// not clamped, so values outside [0, 1] extrapolate along the line.
+    pub fn lerp(&self, other: &Vec3f, t: f64) -> Vec3f {
+        *self * (1.0 - t) + *other * t
+    }
+
+    // Per component, so one large difference can't hide behind small ones as
+    // it could in the norm of the difference.
+    pub fn approx_eq(&self, other: &Vec3f, epsilon: f64) -> bool {
+        (self.x - other.x).abs() <= epsilon &&
+            (self.y - other.y).abs() <= epsilon &&
+            (self.z - other.z).abs() <= epsilon
+    }
+
+    // In radians. The cosine is clamped since rounding can push it just past
+    // 1 for near-parallel vectors, where `acos` would give NaN.
+    pub fn angle_between(&self, other: &Vec3f) -> f64 {
+        (self.dot(other) / (self.norm() * other.norm())).clamp(-1.0, 1.0).acos()
+    }
+
+    // Mirrors the vector about the plane with the given unit normal.
+    pub fn reflect(&self, normal: &Vec3f) -> Vec3f {
+        *self - *normal * (2.0 * self.dot(normal))
+    }
+
+    // `None` for divisors too small to divide by, like in matrix inversion.
+    pub fn checked_div(self, d: f64) -> Option<Vec3f> {
+        if d.abs() < f64::MIN_POSITIVE {
+            None
+        } else {
+            Some(self / d)
+        }
+    }
+
     pub fn cross(&self, other: &Vec3f) -> Vec3f {
         Vec3f::new(
             self.y * other.z - self.z * other.y,
@@ -60,6 +140,20 @@ impl Mul<f64> for Vec3f {
     }
 }
 
+impl Div<f64> for Vec3f {
+    type Output = Vec3f;
+    fn div(self, div: f64) -> Vec3f {
+        Vec3f::new(self.x / div, self.y / div, self.z / div)
+    }
+}
+
+impl Div<Vec3f> for Vec3f {
+    type Output = Vec3f;
+    fn div(self, other: Vec3f) -> Vec3f {
+        Vec3f::new(self.x / other.x, self.y / other.y, self.z / other.z)
+    }
+}
+
 impl Add<Vec3f> for Vec3f {
     type Output = Vec3f;
     fn add(self, other: Vec3f) -> Vec3f {
@@ -74,6 +168,229 @@ impl Sub<Vec3f> for Vec3f {
     }
 }
 
+impl Neg for Vec3f {
+    type Output = Vec3f;
+    fn neg(self) -> Vec3f {
+        Vec3f::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl AddAssign<Vec3f> for Vec3f {
+    fn add_assign(&mut self, other: Vec3f) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign<Vec3f> for Vec3f {
+    fn sub_assign(&mut self, other: Vec3f) {
+        *self = *self - other;
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Vec2f {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vec2f {
+    pub fn new(x: f64, y: f64) -> Vec2f {
+        Vec2f { x, y }
+    }
+
+    pub fn dot(&self, other: &Vec2f) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    // z of the 3D cross product, i.e. twice the signed area of the triangle
+    // spanned by both vectors; positive when `other` is counter-clockwise.
+    pub fn cross(&self, other: &Vec2f) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.length_squared().sqrt()
+    }
+
+    pub fn length_squared(&self) -> f64 {
+        self.dot(self)
+    }
+
+    pub fn distance(&self, other: &Vec2f) -> f64 {
+        (*self - *other).norm()
+    }
+
+    pub fn distance_squared(&self, other: &Vec2f) -> f64 {
+        (*self - *other).length_squared()
+    }
+
+    pub fn normalize(&self) -> Vec2f {
+        *self / self.norm()
+    }
+
+    pub fn try_normalize(&self) -> Option<Vec2f> {
+        let norm = self.norm();
+        if norm < f64::MIN_POSITIVE {
+            None
+        } else {
+            Some(*self / norm)
+        }
+    }
+
+    pub fn lerp(&self, other: &Vec2f, t: f64) -> Vec2f {
+        *self * (1.0 - t) + *other * t
+    }
+
+    pub fn approx_eq(&self, other: &Vec2f, epsilon: f64) -> bool {
+        (self.x - other.x).abs() <= epsilon && (self.y - other.y).abs() <= epsilon
+    }
+}
+
+impl From<[f64; 2]> for Vec2f {
+    fn from(v: [f64; 2]) -> Vec2f {
+        Vec2f::new(v[0], v[1])
+    }
+}
+
+// Drops z, e.g. to get the screen position of a projected vertex.
+impl From<Vec3f> for Vec2f {
+    fn from(v: Vec3f) -> Vec2f {
+        Vec2f::new(v.x, v.y)
+    }
+}
+
+impl From<VecUV2f> for Vec2f {
+    fn from(uv: VecUV2f) -> Vec2f {
+        Vec2f::new(uv.u, uv.v)
+    }
+}
+
+impl From<Vec2f> for VecUV2f {
+    fn from(v: Vec2f) -> VecUV2f {
+        VecUV2f::new(v.x, v.y)
+    }
+}
+
+impl Mul<f64> for Vec2f {
+    type Output = Vec2f;
+    fn mul(self, mul: f64) -> Vec2f {
+        Vec2f::new(self.x * mul, self.y * mul)
+    }
+}
+
+impl Div<f64> for Vec2f {
+    type Output = Vec2f;
+    fn div(self, div: f64) -> Vec2f {
+        Vec2f::new(self.x / div, self.y / div)
+    }
+}
+
+impl Add<Vec2f> for Vec2f {
+    type Output = Vec2f;
+    fn add(self, other: Vec2f) -> Vec2f {
+        Vec2f::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub<Vec2f> for Vec2f {
+    type Output = Vec2f;
+    fn sub(self, other: Vec2f) -> Vec2f {
+        Vec2f::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Neg for Vec2f {
+    type Output = Vec2f;
+    fn neg(self) -> Vec2f {
+        Vec2f::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign<Vec2f> for Vec2f {
+    fn add_assign(&mut self, other: Vec2f) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign<Vec2f> for Vec2f {
+    fn sub_assign(&mut self, other: Vec2f) {
+        *self = *self - other;
+    }
+}
+
+// Axis aligned bounding box. The empty box has `min` above `max` so that
+// extending it by a point gives that point.
+#[derive(Copy, Clone, Debug)]
+pub struct Aabb {
+    pub min: Vec3f,
+    pub max: Vec3f,
+}
+
+impl Aabb {
+    pub fn empty() -> Aabb {
+        Aabb {
+            min: Vec3f::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Vec3f::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Vec3f>) -> Aabb {
+        points.into_iter().fold(Aabb::empty(), |aabb, &point| aabb.extend(point))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn extend(self, point: Vec3f) -> Aabb {
+        Aabb {
+            min: Vec3f::new(self.min.x.min(point.x), self.min.y.min(point.y), self.min.z.min(point.z)),
+            max: Vec3f::new(self.max.x.max(point.x), self.max.y.max(point.y), self.max.z.max(point.z)),
+        }
+    }
+
+    pub fn contains(&self, point: Vec3f) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) &&
+            (self.min.y..=self.max.y).contains(&point.y) &&
+            (self.min.z..=self.max.z).contains(&point.z)
+    }
+
+    pub fn center(&self) -> Vec3f {
+        self.min.lerp(&self.max, 0.5)
+    }
+
+    pub fn size(&self) -> Vec3f {
+        self.max - self.min
+    }
+
+    pub fn union(self, other: Aabb) -> Aabb {
+        self.extend(other.min).extend(other.max)
+    }
+
+    // Slab test: ray parameter at which `origin + t * dir` enters the box,
+    // 0 if the origin is already inside, `None` if the ray misses it or the
+    // box lies behind the origin.
+    pub fn intersect_ray(&self, origin: Vec3f, dir: Vec3f) -> Option<f64> {
+        let (mut t_min, mut t_max) = (0.0_f64, f64::INFINITY);
+        for (o, d, min, max) in [
+            (origin.x, dir.x, self.min.x, self.max.x),
+            (origin.y, dir.y, self.min.y, self.max.y),
+            (origin.z, dir.z, self.min.z, self.max.z),
+        ] {
+            let inv = 1.0 / d;
+            let (t0, t1) = ((min - o) * inv, (max - o) * inv);
+            // a ray parallel to the slab and on its boundary gives NaN, which
+            // min/max then ignore
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+            if t_min > t_max {
+                return None;
+            }
+        }
+        Some(t_min)
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct Triangle {
     p1: Vec3f,
@@ -90,6 +407,10 @@ pub struct Triangle {
     det: f64,
 }
 
+// Largest sine of the angle between two edges of a triangle that still
+// counts as collinear.
+const COLLINEAR_SINE: f64 = 1e-9;
+
 impl Triangle {
     pub fn new(p1: Vec3f, p2: Vec3f, p3: Vec3f) -> Triangle {
         let v0 = p2 - p1;
@@ -104,6 +425,26 @@ impl Triangle {
         Triangle { p1, p2, p3, v0, v1, d00, d01, d11, det }
     }
 
+    // `None` for collinear points or non-finite coordinates, which `new`
+    // accepts but `barycentric` can't handle. Collinear means the sine of the
+    // angle between two edges is within `COLLINEAR_SINE`, so rounding noise
+    // doesn't count as area whatever the triangle's size.
+    pub fn try_new(p1: Vec3f, p2: Vec3f, p3: Vec3f) -> Option<Triangle> {
+        let finite = [p1, p2, p3].iter().all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite());
+        let triangle = Triangle::new(p1, p2, p3);
+        let (v0, v1) = (triangle.v0, triangle.v1);
+        let flat = v0.cross(&v1).norm() <= COLLINEAR_SINE * v0.norm() * v1.norm();
+        (finite && !flat && triangle.det.is_normal()).then_some(triangle)
+    }
+
+    // Whether the triangle has no area once projected onto the xy (screen)
+    // plane, e.g. a face seen edge-on, with the same tolerance as `try_new`.
+    pub fn is_edge_on(&self) -> bool {
+        let (v0, v1) = (self.v0, self.v1);
+        let cross = v0.x * v1.y - v0.y * v1.x;
+        cross.abs() <= COLLINEAR_SINE * v0.x.hypot(v0.y) * v1.x.hypot(v1.y)
+    }
+
     pub fn barycentric(&self, p: Vec3f) -> Option<[f64; 3]> {
         let v2 = p - self.p1;
 
@@ -122,18 +463,76 @@ impl Triangle {
             return None;
         }
 
-        Some([1.0 - v - w, v, w])
+        let u = 1.0 - v - w;
+
+        if u < 0.0 {
+            return None;
+        }
+
+        Some([u, v, w])
     }
 
     pub fn vertices(&self) -> [Vec3f; 3] {
         [self.p1, self.p2, self.p3]
     }
+
+    // Moller-Trumbore: ray parameter of the hit at `origin + t * dir` with
+    // t >= 0, hitting either side of the triangle.
+    pub fn intersect_ray(&self, origin: Vec3f, dir: Vec3f) -> Option<f64> {
+        let p = dir.cross(&self.v1);
+        let det = self.v0.dot(&p);
+        if det.abs() < f64::EPSILON * self.v0.norm() * self.v1.norm() * dir.norm() {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        let s = origin - self.p1;
+        let u = s.dot(&p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(&self.v0);
+        let v = dir.dot(&q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = self.v1.dot(&q) * inv_det;
+        (t >= 0.0).then_some(t)
+    }
+
+    // Yields the pixels covered by the triangle's projection onto the xy-plane
+    // together with their screen-space barycentric coordinates.
+    pub fn rasterize_pixels(&self, width: u32, height: u32) -> impl Iterator<Item = (u32, u32, [f64; 3])> {
+        let [p1, p2, p3] = self.vertices().map(|p| Vec3f::new(p.x, p.y, 0.0));
+        let flat = Triangle::new(p1, p2, p3);
+
+        let (mut min_x, mut min_y) = (width.saturating_sub(1), height.saturating_sub(1));
+        let (mut max_x, mut max_y) = (0u32, 0u32);
+        for vertex in flat.vertices() {
+            min_x = min_x.min(vertex.x as u32);
+            min_y = min_y.min(vertex.y as u32);
+            max_x = width.saturating_sub(1).min(max_x.max(vertex.x as u32));
+            max_y = height.saturating_sub(1).min(max_y.max(vertex.y as u32));
+        }
+        let (max_x, max_y) = if width == 0 || height == 0 { (0, 0) } else { (max_x + 1, max_y + 1) };
+
+        (min_x..max_x).flat_map(move |x| {
+            (min_y..max_y).filter_map(move |y| {
+                flat.barycentric(Vec3f::new(x as f64, y as f64, 0.0))
+                    .map(|bcs| (x, y, bcs))
+            })
+        })
+    }
 }
 
 pub trait SqMatrix<T> : Sized {
     fn dim(&self) -> usize;
     fn invert(&self) -> Option<Self>;
     fn det(&self) -> T;
+    fn transpose(&self) -> Self;
+    fn trace(&self) -> T;
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -146,6 +545,15 @@ impl Mat3x3f {
         Mat3x3f { data: [0.0; 9] }
     }
 
+    pub fn from_rows(rows: [Vec3f; 3]) -> Mat3x3f {
+        let [a, b, c] = rows;
+        Mat3x3f::from([
+            a.x, a.y, a.z,
+            b.x, b.y, b.z,
+            c.x, c.y, c.z,
+        ])
+    }
+
     pub fn identity() -> Mat3x3f {
         Mat3x3f::from([
             1.0, 0.0, 0.0,
@@ -154,6 +562,43 @@ impl Mat3x3f {
         ])
     }
 
+    // Eigen decomposition of a symmetric matrix using cyclic Jacobi rotations.
+    // Returns the eigenvalues and a matrix holding the matching eigenvectors
+    // as columns.
+    pub fn symmetric_eigen(&self) -> (Vec3f, Mat3x3f) {
+        const MAX_SWEEPS: usize = 32;
+
+        let mut a = *self;
+        let mut vectors = Mat3x3f::identity();
+        for _ in 0..MAX_SWEEPS {
+            let off_diagonal = a[0][1].abs() + a[0][2].abs() + a[1][2].abs();
+            if off_diagonal < f64::EPSILON {
+                break;
+            }
+
+            for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                let mut rotation = Mat3x3f::identity();
+                rotation[p][p] = c;
+                rotation[q][q] = c;
+                rotation[p][q] = s;
+                rotation[q][p] = -s;
+
+                a = rotation.transpose() * a * rotation;
+                vectors = vectors * rotation;
+            }
+        }
+
+        (Vec3f::new(a[0][0], a[1][1], a[2][2]), vectors)
+    }
+
     fn cofactor(&self, row: usize, col: usize) -> f64 {
         match (row, col) {
             (0, 0) => self[1][1] * self[2][2] - self[1][2] * self[2][1],
@@ -181,7 +626,8 @@ impl SqMatrix<f64> for Mat3x3f {
         let c02 = self.cofactor(0, 2);
 
         let det = self[0][0] * c00 + self[0][1] * c01 + self[0][2] * c02;
-        if det.abs() < f64::MIN_POSITIVE {
+        // also rejects NaN and infinite determinants from non-finite input
+        if !det.is_normal() {
             None
         } else {
             let c10 = self.cofactor(1, 0);
@@ -207,6 +653,20 @@ impl SqMatrix<f64> for Mat3x3f {
         self[0][1] * self.cofactor(0, 1) +
         self[0][2] * self.cofactor(0, 2)
     }
+
+    fn transpose(&self) -> Self {
+        let mut res = Mat3x3f::new();
+        for row in 0..self.dim() {
+            for col in 0..self.dim() {
+                res[col][row] = self[row][col];
+            }
+        }
+        res
+    }
+
+    fn trace(&self) -> f64 {
+        (0..self.dim()).map(|i| self[i][i]).sum()
+    }
 }
 
 impl From<[f64; 9]> for Mat3x3f {
@@ -232,6 +692,13 @@ impl IndexMut<usize> for Mat3x3f {
 impl Mul for Mat3x3f {
     type Output = Mat3x3f;
     fn mul(self, rhs: Mat3x3f) -> Mat3x3f {
+        Mul::mul(&self, &rhs)
+    }
+}
+
+impl Mul<&Mat3x3f> for &Mat3x3f {
+    type Output = Mat3x3f;
+    fn mul(self, rhs: &Mat3x3f) -> Mat3x3f {
         let mut res = Mat3x3f::new();
         for row in 0..self.dim() {
             for col in 0..self.dim() {
@@ -244,6 +711,35 @@ impl Mul for Mat3x3f {
     }
 }
 
+impl Mul<f64> for Mat3x3f {
+    type Output = Mat3x3f;
+    fn mul(self, rhs: f64) -> Mat3x3f {
+        Mat3x3f::from(self.data.map(|x| x * rhs))
+    }
+}
+
+impl Mul<Vec3f> for Mat3x3f {
+    type Output = Vec3f;
+    fn mul(self, rhs: Vec3f) -> Vec3f {
+        Vec3f::new(
+            self[0][0] * rhs.x + self[0][1] * rhs.y + self[0][2] * rhs.z,
+            self[1][0] * rhs.x + self[1][1] * rhs.y + self[1][2] * rhs.z,
+            self[2][0] * rhs.x + self[2][1] * rhs.y + self[2][2] * rhs.z,
+        )
+    }
+}
+
+impl From<Mat3x3f> for Mat4x4f {
+    fn from(mat: Mat3x3f) -> Mat4x4f {
+        Mat4x4f::from([
+            mat[0][0], mat[0][1], mat[0][2], 0.0,
+            mat[1][0], mat[1][1], mat[1][2], 0.0,
+            mat[2][0], mat[2][1], mat[2][2], 0.0,
+            0.0,       0.0,       0.0,       1.0,
+        ])
+    }
+}
+
 impl Display for Mat3x3f {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         for row in 0..self.dim() {
@@ -275,6 +771,115 @@ impl Mat4x4f {
         ])
     }
 
+    pub fn is_identity(&self) -> bool {
+        self.data == Mat4x4f::identity().data
+    }
+
+    pub fn upper_left(&self) -> Mat3x3f {
+        Mat3x3f::from([
+            self[0][0], self[0][1], self[0][2],
+            self[1][0], self[1][1], self[1][2],
+            self[2][0], self[2][1], self[2][2],
+        ])
+    }
+
+    // Inverse transpose of the upper-left block, which takes normals along
+    // with a model matrix that scales non-uniformly. `None` if that block is
+    // singular.
+    pub fn normal_matrix(&self) -> Option<Mat3x3f> {
+        Some(self.upper_left().invert()?.transpose())
+    }
+
+    // Transforms a direction, w = 0: translation doesn't apply and there is
+    // no divide.
+    pub fn transform_direction(&self, d: Vec3f) -> Vec3f {
+        self.upper_left() * d
+    }
+
+    // Same as `Vec3f::from(self * Mat4x1f::from(p))` for every point,
+    // including the divide by w, without the per-point matrix copies.
+    pub fn transform_points(&self, points: &[Vec3f]) -> Vec<Vec3f> {
+        let mut points = points.to_vec();
+        self.transform_points_mut(&mut points);
+        points
+    }
+
+    pub fn transform_points_mut(&self, points: &mut [Vec3f]) {
+        let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|row| {
+            [self.data[4 * row], self.data[4 * row + 1], self.data[4 * row + 2], self.data[4 * row + 3]]
+        });
+        let dot = |r: [f64; 4], p: &Vec3f| r[0] * p.x + r[1] * p.y + r[2] * p.z + r[3];
+        for p in points.iter_mut() {
+            let w = dot(r3, p);
+            *p = Vec3f::new(dot(r0, p) / w, dot(r1, p) / w, dot(r2, p) / w);
+        }
+    }
+
+    pub fn translation(t: Vec3f) -> Self {
+        Mat4x4f::from([
+            1.0, 0.0, 0.0, t.x,
+            0.0, 1.0, 0.0, t.y,
+            0.0, 0.0, 1.0, t.z,
+            0.0, 0.0, 0.0, 1.0,
+        ])
+    }
+
+    pub fn scale(s: Vec3f) -> Self {
+        Mat4x4f::from([
+            s.x, 0.0, 0.0, 0.0,
+            0.0, s.y, 0.0, 0.0,
+            0.0, 0.0, s.z, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ])
+    }
+
+    // Counter-clockwise rotations by `angle` radians, looking down the axis
+    // towards the origin.
+    pub fn rotation_x(angle: f64) -> Self {
+        let (s, c) = angle.sin_cos();
+        Mat4x4f::from([
+            1.0, 0.0, 0.0, 0.0,
+            0.0, c,   -s,  0.0,
+            0.0, s,   c,   0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ])
+    }
+
+    pub fn rotation_y(angle: f64) -> Self {
+        let (s, c) = angle.sin_cos();
+        Mat4x4f::from([
+            c,   0.0, s,   0.0,
+            0.0, 1.0, 0.0, 0.0,
+            -s,  0.0, c,   0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ])
+    }
+
+    pub fn rotation_z(angle: f64) -> Self {
+        let (s, c) = angle.sin_cos();
+        Mat4x4f::from([
+            c,   -s,  0.0, 0.0,
+            s,   c,   0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ])
+    }
+
+    // View matrix for a camera at `eye` looking towards `center`, OpenGL
+    // style: the camera ends up at the origin looking down -z with `up`
+    // roughly along +y.
+    pub fn look_at(eye: Vec3f, center: Vec3f, up: Vec3f) -> Self {
+        let forward = (center - eye).normalize();
+        let side = forward.cross(&up).normalize();
+        let up = side.cross(&forward);
+        Mat4x4f::from([
+            side.x,     side.y,     side.z,     -side.dot(&eye),
+            up.x,       up.y,       up.z,       -up.dot(&eye),
+            -forward.x, -forward.y, -forward.z, forward.dot(&eye),
+            0.0,        0.0,        0.0,        1.0,
+        ])
+    }
+
     pub fn viewport(x: f64, y: f64, width: f64, height: f64) -> Self {
         Mat4x4f::from([
             width / 2.0, 0.0,          0.0,         x + width / 2.0,
@@ -284,6 +889,25 @@ impl Mat4x4f {
         ])
     }
 
+    // OpenGL style projections: the camera looks down -z and depths between
+    // `near` and `far` map to [-1, 1] after the homogeneous divide.
+    pub fn frustum(left: f64, right: f64, bottom: f64, top: f64, near: f64, far: f64) -> Self {
+        Mat4x4f::from([
+            2.0 * near / (right - left), 0.0, (right + left) / (right - left), 0.0,
+            0.0, 2.0 * near / (top - bottom), (top + bottom) / (top - bottom), 0.0,
+            0.0, 0.0, -(far + near) / (far - near), -2.0 * far * near / (far - near),
+            0.0, 0.0, -1.0, 0.0,
+        ])
+    }
+
+    // `fov_y` is the vertical field of view in radians. A point at z = -near
+    // lands on NDC z = -1 and one at z = -far on +1.
+    pub fn perspective(fov_y: f64, aspect: f64, near: f64, far: f64) -> Self {
+        let top = near * (fov_y / 2.0).tan();
+        let right = top * aspect;
+        Mat4x4f::frustum(-right, right, -top, top, near, far)
+    }
+
     fn cofactor(&self, row: usize, col: usize) -> f64 {
         match (row, col) {
             (0, 0) => Mat3x3f::from([
@@ -383,7 +1007,8 @@ impl SqMatrix<f64> for Mat4x4f {
         let c03 = self.cofactor(0, 3);
 
         let det = self[0][0] * c00 + self[0][1] * c01 + self[0][2] * c02 + self[0][3] * c03;
-        if det.abs() < f64::MIN_POSITIVE {
+        // also rejects NaN and infinite determinants from non-finite input
+        if !det.is_normal() {
             None
         } else {
             let c10 = self.cofactor(1, 0);
@@ -418,6 +1043,20 @@ impl SqMatrix<f64> for Mat4x4f {
         self[0][2] * self.cofactor(0, 2) +
         self[0][3] * self.cofactor(0, 3)
     }
+
+    fn transpose(&self) -> Self {
+        let mut res = Mat4x4f::new();
+        for row in 0..self.dim() {
+            for col in 0..self.dim() {
+                res[col][row] = self[row][col];
+            }
+        }
+        res
+    }
+
+    fn trace(&self) -> f64 {
+        (0..self.dim()).map(|i| self[i][i]).sum()
+    }
 }
 
 impl From<[f64; 16]> for Mat4x4f {
@@ -443,6 +1082,13 @@ impl IndexMut<usize> for Mat4x4f {
 impl Mul for Mat4x4f {
     type Output = Mat4x4f;
     fn mul(self, rhs: Mat4x4f) -> Mat4x4f {
+        Mul::mul(&self, &rhs)
+    }
+}
+
+impl Mul<&Mat4x4f> for &Mat4x4f {
+    type Output = Mat4x4f;
+    fn mul(self, rhs: &Mat4x4f) -> Mat4x4f {
         let mut res = Mat4x4f::new();
         for row in 0..4 {
             for col in 0..4 {
@@ -456,6 +1102,13 @@ impl Mul for Mat4x4f {
     }
 }
 
+impl Mul<f64> for Mat4x4f {
+    type Output = Mat4x4f;
+    fn mul(self, rhs: f64) -> Mat4x4f {
+        Mat4x4f::from(self.data.map(|x| x * rhs))
+    }
+}
+
 impl Display for Mat4x4f {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         for row in 0..self.dim() {
@@ -477,6 +1130,14 @@ impl Mat4x1f {
     pub fn new() -> Mat4x1f {
         Mat4x1f { data: [0.0; 4] }
     }
+
+    /// Takes x, y and z as they are, without the homogeneous divide.
+    ///
+    /// Use this for affine transforms (where w stays 1) and for directions or
+    /// normals (w == 0); use `Vec3f::from` after a perspective projection.
+    pub fn to_vec3_affine(&self) -> Vec3f {
+        Vec3f::new(self[0][0], self[1][0], self[2][0])
+    }
 }
 
 impl From<[f64; 4]> for Mat4x1f {
@@ -533,6 +1194,15 @@ impl Mul<Mat4x1f> for Mat4x4f {
     }
 }
 
+// Transforms a point, w = 1, including the divide by w; use `Mul<Mat4x1f>`
+// for the homogeneous result.
+impl Mul<Vec3f> for Mat4x4f {
+    type Output = Vec3f;
+    fn mul(self, rhs: Vec3f) -> Vec3f {
+        Vec3f::from(self * Mat4x1f::from(rhs))
+    }
+}
+
 impl Display for Mat4x1f {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         for row in 0..4 {
@@ -583,6 +1253,39 @@ impl MatNxMf {
             self[target_row][col] -= scalar * self[source_row][col];
         }
     }
+
+    // Reduces the leading square block to the identity, applying the same
+    // row operations to the columns right of it. `false` if that block is
+    // singular.
+    fn gauss_jordan(&mut self) -> bool {
+        let n = self.rows;
+        for i in 0..n {
+            if self[i][i].abs() < f64::MIN_POSITIVE {
+                let mut pivot_row = i;
+                for j in i + 1..n {
+                    if !(self[j][i].abs() < f64::MIN_POSITIVE) {
+                        pivot_row = j;
+                        break;
+                    }
+                }
+                if pivot_row == i {
+                    return false;
+                }
+                self.swap_rows(i, pivot_row);
+            }
+
+            let pivot_value = self[i][i];
+            self.scale_row(i, 1.0 / pivot_value);
+
+            for j in 0..n {
+                if j != i {
+                    let factor = self[j][i];
+                    self.subtract_scaled(j, i, factor);
+                }
+            }
+        }
+        true
+    }
 }
 
 impl Index<usize> for MatNxMf {
@@ -622,6 +1325,26 @@ impl MatNxNf {
         MatNxNf { dim, data }
     }
 
+    // Panics unless there are as many rows as each row has entries.
+    pub fn from_rows(rows: &[Vec<f64>]) -> MatNxNf {
+        let dim = rows.len();
+        for (index, row) in rows.iter().enumerate() {
+            assert_eq!(row.len(), dim, "Row {} has {} entries, expected {} for a square matrix",
+                       index, row.len(), dim);
+        }
+        MatNxNf::new(dim, rows.concat())
+    }
+
+    // Panics unless there are as many columns as each column has entries.
+    pub fn from_cols(cols: &[Vec<f64>]) -> MatNxNf {
+        let dim = cols.len();
+        for (index, col) in cols.iter().enumerate() {
+            assert_eq!(col.len(), dim, "Column {} has {} entries, expected {} for a square matrix",
+                       index, col.len(), dim);
+        }
+        MatNxNf::new(dim, cols.concat()).transpose()
+    }
+
     pub fn identity(dim: usize) -> Self {
         let mut data = vec![0.0; dim * dim];
         for i in 0..dim {
@@ -629,6 +1352,107 @@ impl MatNxNf {
         }
         MatNxNf { dim, data }
     }
+
+    pub fn is_finite(&self) -> bool {
+        self.data.iter().all(|x| x.is_finite())
+    }
+
+    // Solves `self * x = rhs` by eliminating on `[self | rhs]`, without
+    // forming the inverse. `None` for singular or non-finite systems, like
+    // `invert`.
+    pub fn solve(&self, rhs: &[f64]) -> Option<Vec<f64>> {
+        assert_eq!(self.dim, rhs.len(), "Right-hand side should match matrix dimension");
+        if !self.is_finite() || !rhs.iter().all(|x| x.is_finite()) {
+            return None;
+        }
+        let n = self.dim;
+
+        let mut data = Vec::with_capacity(n * (n + 1));
+        for (row, &b) in rhs.iter().enumerate() {
+            data.extend_from_slice(&self[row]);
+            data.push(b);
+        }
+        let mut aug = MatNxMf::new(n, n + 1, data);
+        if !aug.gauss_jordan() {
+            return None;
+        }
+        Some((0..n).map(|row| aug[row][n]).collect())
+    }
+
+    // LU decomposition with partial pivoting, `P * self = L * U`, with `L`
+    // unit lower triangular and `U` upper triangular. The permutation is
+    // given as the original row index of each row of `P * self`. `None` for
+    // non-finite matrices and for singular ones, judged by pivots that
+    // vanish relative to the largest entry so that rounding doesn't hide
+    // them.
+    pub fn lu(&self) -> Option<(MatNxNf, MatNxNf, Vec<usize>)> {
+        if !self.is_finite() {
+            return None;
+        }
+        let n = self.dim;
+        let largest = self.data.iter().fold(0.0_f64, |largest, x| largest.max(x.abs()));
+        let tolerance = (n as f64 * f64::EPSILON * largest).max(f64::MIN_POSITIVE);
+        let mut upper = MatNxMf::new(n, n, self.data.clone());
+        let mut lower = MatNxMf::new(n, n, vec![0.0; n * n]);
+        let mut permutation: Vec<usize> = (0..n).collect();
+
+        for i in 0..n {
+            let pivot_row = (i..n)
+                .max_by(|&a, &b| upper[a][i].abs().total_cmp(&upper[b][i].abs()))
+                .unwrap();
+            if upper[pivot_row][i].abs() < tolerance {
+                return None;
+            }
+            upper.swap_rows(i, pivot_row);
+            lower.swap_rows(i, pivot_row);
+            permutation.swap(i, pivot_row);
+
+            let pivot_value = upper[i][i];
+            for j in i + 1..n {
+                let factor = upper[j][i] / pivot_value;
+                lower[j][i] = factor;
+                upper.subtract_scaled(j, i, factor);
+            }
+        }
+
+        for i in 0..n {
+            lower[i][i] = 1.0;
+        }
+        Some((MatNxNf::new(n, lower.data), MatNxNf::new(n, upper.data), permutation))
+    }
+
+    /// Cheap estimate of the condition number: the ratio of the largest to the
+    /// smallest pivot magnitude met during elimination with partial pivoting.
+    /// This is only an approximation, not the true 2-norm condition number,
+    /// but it grows the same way as the matrix approaches singularity.
+    /// Singular matrices give `f64::INFINITY`.
+    pub fn condition_estimate(&self) -> f64 {
+        let n = self.dim;
+        let mut aux = MatNxMf::new(n, n, self.data.clone());
+
+        let mut largest = 0.0_f64;
+        let mut smallest = f64::INFINITY;
+        for i in 0..n {
+            let pivot_row = (i..n)
+                .max_by(|&a, &b| aux[a][i].abs().total_cmp(&aux[b][i].abs()))
+                .unwrap();
+            aux.swap_rows(i, pivot_row);
+
+            let pivot_value = aux[i][i];
+            if pivot_value.abs() < f64::MIN_POSITIVE {
+                return f64::INFINITY;
+            }
+            largest = largest.max(pivot_value.abs());
+            smallest = smallest.min(pivot_value.abs());
+
+            for j in i + 1..n {
+                let factor = aux[j][i];
+                aux.subtract_scaled(j, i, factor / pivot_value);
+            }
+        }
+
+        largest / smallest
+    }
 }
 
 impl SqMatrix<f64> for MatNxNf {
@@ -637,33 +1461,14 @@ impl SqMatrix<f64> for MatNxNf {
     }
 
     fn invert(&self) -> Option<Self> {
+        if !self.is_finite() {
+            return None;
+        }
         let n = self.dim;
 
         let mut aug = MatNxMf::augmented(self);
-        for i in 0..n {
-            if aug[i][i].abs() < f64::MIN_POSITIVE {
-                let mut pivot_row = i;
-                for j in i + 1..n {
-                    if !(aug[j][i].abs() < f64::MIN_POSITIVE) {
-                        pivot_row = j;
-                        break;
-                    }
-                }
-                if pivot_row == i {
-                    return None;
-                }
-                aug.swap_rows(i, pivot_row);
-            }
-
-            let pivot_value = aug[i][i];
-            aug.scale_row(i, 1.0 / pivot_value);
-
-            for j in 0..n {
-                if j != i {
-                    let factor = aug[j][i];
-                    aug.subtract_scaled(j, i, factor);
-                }
-            }
+        if !aug.gauss_jordan() {
+            return None;
         }
 
         for i in 0..n {
@@ -675,6 +1480,9 @@ impl SqMatrix<f64> for MatNxNf {
     }
 
     fn det(&self) -> f64 {
+        if !self.is_finite() {
+            return f64::NAN;
+        }
         let n = self.dim;
         let mut det = 1.0;
 
@@ -706,6 +1514,20 @@ impl SqMatrix<f64> for MatNxNf {
         det *= aux[n - 1][n - 1];
         det
     }
+
+    fn transpose(&self) -> Self {
+        let mut res = MatNxNf::new(self.dim, vec![0.0; self.dim * self.dim]);
+        for row in 0..self.dim {
+            for col in 0..self.dim {
+                res[col][row] = self[row][col];
+            }
+        }
+        res
+    }
+
+    fn trace(&self) -> f64 {
+        (0..self.dim()).map(|i| self[i][i]).sum()
+    }
 }
 
 impl Index<usize> for MatNxNf {
@@ -724,8 +1546,10 @@ impl IndexMut<usize> for MatNxNf {
 impl Mul for MatNxNf {
     type Output = MatNxNf;
     fn mul(self, rhs: MatNxNf) -> MatNxNf {
-        let mut res = MatNxNf::new(self.dim, vec![0.0; self.dim * rhs.dim]);
+        assert_eq!(self.dim, rhs.dim, "Cannot multiply a {0}x{0} matrix by a {1}x{1} matrix",
+                   self.dim, rhs.dim);
         let n = self.dim;
+        let mut res = MatNxNf::new(n, vec![0.0; n * n]);
         for row in 0..n {
             for col in 0..n {
                 for idx in 0..n {
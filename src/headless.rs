@@ -0,0 +1,37 @@
+use crate::common::Resolution;
+use crate::framebuffer::{FrameBuffer, Rgb};
+use crate::geometry::{Mat4x4f, Vec3f};
+use crate::model::Model;
+use crate::renderer::Renderer;
+use crate::zbuffer::ZBuffer;
+
+// Entry points for rendering straight into a `FrameBuffer`, with no SDL types
+// involved, for embedding the rasterizer elsewhere. Built with
+// `--no-default-features` the crate doesn't depend on SDL at all, e.g. for
+// wasm32-unknown-unknown; `FrameBuffer::to_rgba8` then hands the pixels over.
+
+// Maps clip space onto the middle three quarters of the frame, as the viewer
+// does.
+pub fn view_port(resolution: Resolution) -> Mat4x4f {
+    Mat4x4f::viewport(
+        resolution.width as f64 / 8.0,
+        resolution.height as f64 / 8.0,
+        resolution.width as f64 * 3.0 / 4.0,
+        resolution.height as f64 * 3.0 / 4.0
+    )
+}
+
+// Clears `framebuffer` to black and renders `model` into it with `renderer`'s
+// settings, using a depth buffer of the frame's size cleared for the
+// renderer's depth function.
+pub fn render_model(renderer: &Renderer,
+                    framebuffer: &mut FrameBuffer,
+                    model: &Model,
+                    light_direction: Vec3f,
+                    projection: Mat4x4f) -> Result<(), String> {
+    let resolution = framebuffer.resolution();
+    let mut zbuffer = ZBuffer::new(resolution, renderer.depth_clear_value());
+    framebuffer.clear(Rgb::default(), &mut zbuffer);
+    renderer.render_model(framebuffer, &mut zbuffer, &light_direction, model,
+                          view_port(resolution), projection)
+}
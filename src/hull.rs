@@ -0,0 +1,128 @@
+use std::collections::HashSet;
+
+use crate::geometry::Vec3f;
+use crate::model::Model;
+
+const RELATIVE_EPSILON: f64 = 1e-10;
+
+struct HullFace {
+    indices: [usize; 3],
+    normal: Vec3f,
+    offset: f64,
+}
+
+impl HullFace {
+    fn new(points: &[Vec3f], indices: [usize; 3]) -> HullFace {
+        let [a, b, c] = indices.map(|i| points[i]);
+        let normal = (b - a).cross(&(c - a)).normalize();
+        HullFace { indices, normal, offset: normal.dot(&a) }
+    }
+
+    fn distance(&self, point: Vec3f) -> f64 {
+        self.normal.dot(&point) - self.offset
+    }
+}
+
+impl Model {
+    // Incremental 3D convex hull of the vertices, returned as an outward facing
+    // triangle mesh. Each point is tested against every hull face, so this is
+    // O(n^2) in the worst case, which is fine for proxies but slow for dense
+    // scans. Points on or within a small tolerance of the hull are dropped, so
+    // coplanar hull faces come out triangulated. Input without volume
+    // (fewer than four points, or all of them collinear or coplanar) yields
+    // an empty model.
+    pub fn convex_hull(&self) -> Model {
+        let mut seen = HashSet::new();
+        let points: Vec<Vec3f> = self.vertices().iter()
+            .filter(|v| seen.insert((v.x.to_bits(), v.y.to_bits(), v.z.to_bits())))
+            .copied()
+            .collect();
+
+        let empty = || Model::from_parts(Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        let Some(mut faces) = initial_tetrahedron(&points) else {
+            return empty();
+        };
+
+        let extent = points.iter().map(|p| p.norm()).fold(0.0, f64::max);
+        let epsilon = RELATIVE_EPSILON * extent.max(1.0);
+
+        for (index, &point) in points.iter().enumerate() {
+            let visible: Vec<bool> = faces.iter().map(|face| face.distance(point) > epsilon).collect();
+            if !visible.contains(&true) {
+                continue;
+            }
+
+            // edges of the visible region whose twin belongs to a hidden face
+            let visible_edges: HashSet<(usize, usize)> = faces.iter().zip(&visible)
+                .filter(|(_, &v)| v)
+                .flat_map(|(face, _)| edges(face.indices))
+                .collect();
+            let horizon: Vec<(usize, usize)> = visible_edges.iter()
+                .filter(|&&(a, b)| !visible_edges.contains(&(b, a)))
+                .copied()
+                .collect();
+
+            let mut flags = visible.iter();
+            faces.retain(|_| !flags.next().unwrap());
+            faces.extend(horizon.into_iter().map(|(a, b)| HullFace::new(&points, [a, b, index])));
+        }
+
+        let mut remap = vec![usize::MAX; points.len()];
+        let mut vertices = Vec::new();
+        let faces = faces.iter()
+            .map(|face| face.indices.iter()
+                .map(|&i| {
+                    if remap[i] == usize::MAX {
+                        remap[i] = vertices.len();
+                        vertices.push(points[i]);
+                    }
+                    remap[i]
+                })
+                .collect())
+            .collect();
+
+        Model::from_parts(vertices, Vec::new(), Vec::new(), faces)
+    }
+}
+
+fn edges([a, b, c]: [usize; 3]) -> [(usize, usize); 3] {
+    [(a, b), (b, c), (c, a)]
+}
+
+fn initial_tetrahedron(points: &[Vec3f]) -> Option<Vec<HullFace>> {
+    if points.len() < 4 {
+        return None;
+    }
+
+    let extent = points.iter().map(|p| p.norm()).fold(0.0, f64::max).max(1.0);
+    let epsilon = RELATIVE_EPSILON * extent;
+    let farthest = |score: &dyn Fn(Vec3f) -> f64| {
+        (0..points.len()).max_by(|&a, &b| score(points[a]).total_cmp(&score(points[b]))).unwrap()
+    };
+
+    let a = 0;
+    let b = farthest(&|p| p.distance(&points[a]));
+    let ab = points[b] - points[a];
+    if ab.norm() <= epsilon {
+        return None;
+    }
+
+    let c = farthest(&|p| ab.cross(&(p - points[a])).norm());
+    let normal = ab.cross(&(points[c] - points[a]));
+    if normal.norm() <= epsilon * ab.norm() {
+        return None;
+    }
+
+    let normal = normal.normalize();
+    let d = farthest(&|p| normal.dot(&(p - points[a])).abs());
+    if normal.dot(&(points[d] - points[a])).abs() <= epsilon {
+        return None;
+    }
+
+    // orient the base away from the apex so that all faces point outwards
+    let (b, c) = if normal.dot(&(points[d] - points[a])) > 0.0 { (c, b) } else { (b, c) };
+    Some([[a, b, c], [a, d, b], [b, d, c], [c, d, a]]
+        .into_iter()
+        .map(|indices| HullFace::new(points, indices))
+        .collect())
+}
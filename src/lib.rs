@@ -1,6 +1,23 @@
+pub mod adjacency;
+pub mod bvh;
 pub mod common;
 pub mod errors;
+#[cfg(feature = "sdl")]
 pub mod canvas;
+pub mod font;
+pub mod framebuffer;
 pub mod geometry;
+pub mod headless;
+pub mod hull;
+pub mod material;
 pub mod model;
-pub mod renderer;
\ No newline at end of file
+pub mod oit;
+pub mod overdraw;
+pub mod palette;
+pub mod primitives;
+pub mod renderer;
+pub mod scene;
+pub mod shader;
+pub mod shadow;
+pub mod texture;
+pub mod zbuffer;
\ No newline at end of file
@@ -0,0 +1,87 @@
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::errors::RenderError;
+use crate::geometry::Vec3f;
+use crate::model::{parse_vec3f, split_statement};
+
+#[derive(Clone, Debug)]
+pub struct Material {
+    pub name: String,
+    pub diffuse_map: Option<PathBuf>,
+    // Ka, Kd and Ks, 0..1 per channel
+    pub ambient: Vec3f,
+    pub diffuse: Vec3f,
+    pub specular: Vec3f,
+    // Ns, the specular exponent
+    pub shininess: f64,
+}
+
+// White diffuse, so that materials without a `Kd` look like faces without a
+// material.
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            name: String::new(),
+            diffuse_map: None,
+            ambient: Vec3f::new(0.0, 0.0, 0.0),
+            diffuse: Vec3f::new(1.0, 1.0, 1.0),
+            specular: Vec3f::new(0.0, 0.0, 0.0),
+            shininess: 0.0,
+        }
+    }
+}
+
+impl Material {
+    pub fn new(name: &str) -> Self {
+        Material {
+            name: String::from(name),
+            ..Default::default()
+        }
+    }
+
+    // Paths inside the library (texture maps) are resolved against `base_dir`,
+    // which is the directory of the OBJ file referencing the library.
+    pub fn from_file<P>(filename: P, base_dir: &Path) -> Result<Vec<Material>, RenderError>
+        where P: AsRef<Path>, {
+
+        let filename = filename.as_ref();
+        let file = File::open(filename).map_err(|err| {
+            RenderError::MaterialFileError(format!("{}: {}", filename.display(), err))
+        })?;
+        let file = BufReader::new(file);
+
+        let mut materials: Vec<Material> = Vec::new();
+
+        for (line, maybe_line) in file.lines().enumerate() {
+            let text = maybe_line?;
+            if let Some((first, rest)) = split_statement(&text) {
+                let parsed = match (first, materials.last_mut()) {
+                    ("newmtl", _) => {
+                        materials.push(Material::new(rest.trim()));
+                        Ok(())
+                    }
+                    ("map_Kd", Some(material)) => {
+                        material.diffuse_map = Some(base_dir.join(rest.trim()));
+                        Ok(())
+                    }
+                    ("Ka", Some(material)) => parse_vec3f(rest).map(|ka| material.ambient = ka),
+                    ("Kd", Some(material)) => parse_vec3f(rest).map(|kd| material.diffuse = kd),
+                    ("Ks", Some(material)) => parse_vec3f(rest).map(|ks| material.specular = ks),
+                    ("Ns", Some(material)) => rest.trim()
+                        .parse::<f64>()
+                        .map(|ns| material.shininess = ns)
+                        .map_err(|err| format!("invalid Ns format: {}", err)),
+                    _ => Ok(())
+                };
+                parsed.map_err(|msg| RenderError::MaterialParsingError(
+                    format!("{} at line {}: {} in {:?}", filename.display(), line + 1, msg,
+                            text.trim())))?;
+            }
+        }
+
+        Ok(materials)
+    }
+}
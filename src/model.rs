@@ -1,13 +1,18 @@
+use std::cell::OnceCell;
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::io::BufRead;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::adjacency::Adjacency;
 use crate::errors::RenderError;
-use crate::geometry::{VecUV2f, Vec3f};
+use crate::geometry::{Aabb, Mat3x3f, SqMatrix, Triangle, VecUV2f, Vec3f};
+use crate::material::Material;
 
 enum Coordinate {
-    X, Y, Z, U, V,
+    X, Y, Z, W, U, V, R, G, B,
 }
 
 impl Display for Coordinate {
@@ -16,26 +21,44 @@ impl Display for Coordinate {
             Coordinate::X => write!(f, "x"),
             Coordinate::Y => write!(f, "y"),
             Coordinate::Z => write!(f, "z"),
+            Coordinate::W => write!(f, "w"),
             Coordinate::U => write!(f, "u"),
             Coordinate::V => write!(f, "v"),
+            Coordinate::R => write!(f, "r"),
+            Coordinate::G => write!(f, "g"),
+            Coordinate::B => write!(f, "b"),
         }
     }
 }
 
 impl Coordinate {
+    // 1-based position among the arguments of its statement.
+    fn position(&self) -> usize {
+        match self {
+            Coordinate::X | Coordinate::U => 1,
+            Coordinate::Y | Coordinate::V => 2,
+            Coordinate::Z => 3,
+            Coordinate::W | Coordinate::R => 4,
+            Coordinate::G => 5,
+            Coordinate::B => 6,
+        }
+    }
+
     fn parse<'a, I>(&self, iter: &mut I) -> Result<f64, String>
     where I: Iterator<Item = &'a str> {
-        let res = iter
+        let position = self.position();
+        let token = iter
             .next()
-            .ok_or(format!("missing coordinate {self}"))?
+            .ok_or(format!("missing coordinate {self} (argument {position})"))?;
+        let res = token
             .parse::<f64>()
-            .map_err(|err| format!("invalid coordinate {self} format: {}", err))?;
+            .map_err(|err| format!("invalid coordinate {self} format {token:?} (argument {position}): {err}"))?;
 
         Ok(res)
     }
 }
 
-fn parse_vec3f(line: &str) -> Result<Vec3f, String> {
+pub(crate) fn parse_vec3f(line: &str) -> Result<Vec3f, String> {
     let mut parts = line
         .split_whitespace()
         .filter(|s| !s.is_empty());
@@ -45,6 +68,34 @@ fn parse_vec3f(line: &str) -> Result<Vec3f, String> {
                   Coordinate::Z.parse(&mut parts)?))
 }
 
+// Position of a `v` line, divided by the optional fourth value w, with the
+// color of the `v x y z r g b` extension when there are exactly six values.
+fn parse_vertex(line: &str) -> Result<(Vec3f, Option<Vec3f>), String> {
+    let mut parts = line
+        .split_whitespace()
+        .filter(|s| !s.is_empty());
+
+    let vertex = Vec3f::new(Coordinate::X.parse(&mut parts)?,
+                            Coordinate::Y.parse(&mut parts)?,
+                            Coordinate::Z.parse(&mut parts)?);
+    let rest: Vec<&str> = parts.collect();
+    if rest.len() == 1 {
+        let w = Coordinate::W.parse(&mut rest.into_iter())?;
+        if w == 0.0 {
+            return Err(String::from("w is zero"));
+        }
+        return Ok((vertex / w, None));
+    }
+    if rest.len() != 3 {
+        return Ok((vertex, None));
+    }
+    let mut parts = rest.into_iter();
+    let color = Vec3f::new(Coordinate::R.parse(&mut parts)?,
+                           Coordinate::G.parse(&mut parts)?,
+                           Coordinate::B.parse(&mut parts)?);
+    Ok((vertex, Some(color)))
+}
+
 fn parse_vec_uv_2f(line: &str) -> Result<VecUV2f, String> {
     let mut parts = line
         .split_whitespace()
@@ -54,6 +105,38 @@ fn parse_vec_uv_2f(line: &str) -> Result<VecUV2f, String> {
                     Coordinate::V.parse(&mut parts)?))
 }
 
+// Keyword and arguments of an OBJ or MTL line, without any `#` comment and
+// surrounding whitespace. `None` for lines that are empty once stripped or
+// have no arguments.
+pub(crate) fn split_statement(line: &str) -> Option<(&str, &str)> {
+    let line = line.split_once('#').map_or(line, |(statement, _)| statement).trim();
+    line.split_once(char::is_whitespace)
+}
+
+// Reader over an OBJ file, decompressing it on the fly when it is gzipped
+// (going by the extension or the magic bytes).
+fn open(filename: &Path) -> Result<Box<dyn BufRead>, RenderError> {
+    let mut file = BufReader::new(File::open(filename)?);
+    let gzipped = filename.extension().is_some_and(|extension| extension == "gz") ||
+        file.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+    if gzipped {
+        gunzip(file, filename)
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+#[cfg(feature = "flate2")]
+fn gunzip(file: BufReader<File>, _filename: &Path) -> Result<Box<dyn BufRead>, RenderError> {
+    Ok(Box::new(BufReader::new(flate2::bufread::GzDecoder::new(file))))
+}
+
+#[cfg(not(feature = "flate2"))]
+fn gunzip(_file: BufReader<File>, filename: &Path) -> Result<Box<dyn BufRead>, RenderError> {
+    Err(std::io::Error::other(format!("{} is gzip compressed, which needs the flate2 feature",
+                                      filename.display())).into())
+}
+
 enum FaceIndex {
     Vertex,
     Texture,
@@ -61,13 +144,15 @@ enum FaceIndex {
 }
 
 impl FaceIndex {
-    fn parse<'a, I>(&self, iter: &mut I) -> Result<usize, String>
+    // Index as written in the file: 1-based, or negative counting back from
+    // the last element read so far. 0 stands for a missing texture or normal.
+    fn parse<'a, I>(&self, iter: &mut I) -> Result<i64, String>
     where I: Iterator<Item = &'a str>{
         match self {
             FaceIndex::Vertex => iter.next()
                 .map(|elem| {
-                    elem.parse::<usize>()
-                        .map_err(|err| format!("invalid vertex index format: {}", err))
+                    elem.parse::<i64>()
+                        .map_err(|err| format!("invalid vertex index format {:?}: {}", elem, err))
                 })
                 .ok_or("missing vertex index")?,
             FaceIndex::Texture => iter.next()
@@ -75,9 +160,9 @@ impl FaceIndex {
                         if elem.is_empty() {
                             Ok(0)
                         } else {
-                            elem.parse::<usize>()
+                            elem.parse::<i64>()
                                 .map_err(|err| {
-                                    format!("invalid texture index format: {}", err)
+                                    format!("invalid texture index format {:?}: {}", elem, err)
                                 })
                         }
                     })
@@ -87,9 +172,9 @@ impl FaceIndex {
                     if elem.is_empty() {
                         Ok(0)
                     } else {
-                        elem.parse::<usize>()
+                        elem.parse::<i64>()
                             .map_err(|err| {
-                                format!("invalid normal index format: {}", err)
+                                format!("invalid normal index format {:?}: {}", elem, err)
                             })
                     }
                 })
@@ -98,70 +183,234 @@ impl FaceIndex {
     }
 }
 
+// 0-based position of an OBJ index into a table of `len` elements, `None`
+// when it falls outside of it.
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let resolved = if index < 0 { len as i64 + index } else { index - 1 };
+    (0..len as i64).contains(&resolved).then_some(resolved as usize)
+}
+
+// A polygon as indices into its model's tables, resolved by the accessors
+// taking the model.
+#[derive(Clone, Debug)]
 pub struct Face {
-    pub vertices: Vec<Vec3f>,
-    pub textures: Vec<VecUV2f>,
-    pub normals:  Vec<Vec3f>,
+    // 0-based indices of the corners in the model's vertex table
+    pub vertex_indices: Vec<usize>,
+    // into the texture and normal tables, empty unless every corner has one
+    pub texture_indices: Vec<usize>,
+    pub normal_indices: Vec<usize>,
+    // 0 means smoothing is off and the face is shaded flat
+    pub smoothing_group: u32,
+    // index into the model's materials of the `usemtl` in effect
+    pub material: Option<usize>,
 }
 
 impl Face {
     fn from(line: &str,
-            vertices: &Vec<Vec3f>,
-            textures: &Vec<VecUV2f>,
-            normals: &Vec<Vec3f>) -> Result<Face, String> {
+            vertices: usize,
+            textures: usize,
+            normals: usize) -> Result<Face, String> {
         let parts = line
             .split_whitespace()
             .filter(|s| !s.is_empty());
 
-        let mut face_vertices= Vec::new();
-        let mut face_textures = Vec::new();
-        let mut face_normals = Vec::new();
+        let mut vertex_indices = Vec::new();
+        let mut texture_indices = Vec::new();
+        let mut normal_indices = Vec::new();
 
         for part in parts.into_iter() {
             let mut indices = part.split('/');
 
             let vertex_index = FaceIndex::Vertex.parse(&mut indices)?;
-            if vertex_index == 0 || vertex_index > vertices.len() {
-                return Err(format!("face index out of bounds: {}", vertex_index));
-            }
-            face_vertices.push(vertices[vertex_index - 1]);
+            let vertex_index = resolve_index(vertex_index, vertices)
+                .ok_or(format!("face index out of bounds: {}", vertex_index))?;
+            vertex_indices.push(vertex_index);
 
             let texture_index = FaceIndex::Texture.parse(&mut indices)?;
-            if texture_index > textures.len() {
-                return Err(format!("texture index out of bounds: {}", texture_index));
-            }
-            if texture_index > 0 {
-                face_textures.push(textures[texture_index - 1]);
+            if texture_index != 0 {
+                let texture_index = resolve_index(texture_index, textures)
+                    .ok_or(format!("texture index out of bounds: {}", texture_index))?;
+                texture_indices.push(texture_index);
             }
 
             let normal_index = FaceIndex::Normal.parse(&mut indices)?;
-            if normal_index > normals.len() {
-                return Err(format!("normal index out of bounds: {}", normal_index));
-            }
-            if normal_index > 0 {
-                face_normals.push(normals[normal_index - 1]);
+            if normal_index != 0 {
+                let normal_index = resolve_index(normal_index, normals)
+                    .ok_or(format!("normal index out of bounds: {}", normal_index))?;
+                normal_indices.push(normal_index);
             }
         }
 
-        if face_textures.len() != face_vertices.len() {
-            std::mem::swap(&mut face_textures, & mut Vec::new());
+        if texture_indices.len() != vertex_indices.len() {
+            texture_indices.clear();
         }
 
-        if face_normals.len() != face_vertices.len() {
-            std::mem::swap(&mut face_normals, & mut Vec::new());
+        if normal_indices.len() != vertex_indices.len() {
+            normal_indices.clear();
         }
 
         Ok(Face {
-            vertices: face_vertices,
-            textures: face_textures,
-            normals: face_normals,
+            vertex_indices,
+            texture_indices,
+            normal_indices,
+            smoothing_group: 0,
+            material: None,
+        })
+
+    }
+}
+
+// The vertex table may repeat a position, so shared vertices are recognized
+// by their exact bits.
+fn vertex_key(v: &Vec3f) -> (u64, u64, u64) {
+    (v.x.to_bits(), v.y.to_bits(), v.z.to_bits())
+}
+
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+fn parse_smoothing_group(line: &str) -> Result<u32, String> {
+    match line.trim() {
+        "off" => Ok(0),
+        group => group.parse::<u32>().map_err(|e| format!("{}: {}", group, e)),
+    }
+}
+
+fn parse_polyline(line: &str, vertices: &[Vec3f]) -> Result<Vec<usize>, String> {
+    let polyline = line
+        .split_whitespace()
+        .map(|part| {
+            let mut indices = part.split('/');
+            let vertex_index = FaceIndex::Vertex.parse(&mut indices)?;
+            resolve_index(vertex_index, vertices.len())
+                .ok_or(format!("line index out of bounds: {}", vertex_index))
         })
+        .collect::<Result<Vec<usize>, String>>()?;
+
+    if polyline.len() < 2 {
+        return Err(String::from("polyline needs at least two vertices"));
+    }
+
+    Ok(polyline)
+}
+
+// Screen-space winding of a model's front faces, checked against the
+// renderer's `CullMode`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FrontFace {
+    #[default]
+    CounterClockwise,
+    Clockwise,
+}
 
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModelStats {
+    pub vertices: usize,
+    pub normals: usize,
+    pub textures: usize,
+    pub faces: usize,
+}
+
+impl Face {
+    // Number of corners.
+    pub fn len(&self) -> usize {
+        self.vertex_indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertex_indices.is_empty()
+    }
+
+    pub fn vertex(&self, model: &Model, corner: usize) -> Vec3f {
+        model.vertices[self.vertex_indices[corner]]
+    }
+
+    pub fn vertices<'a>(&'a self, model: &'a Model) -> impl Iterator<Item = Vec3f> + 'a {
+        self.vertex_indices.iter().map(|&index| model.vertices[index])
+    }
+
+    pub fn has_textures(&self) -> bool {
+        !self.texture_indices.is_empty()
+    }
+
+    pub fn texture(&self, model: &Model, corner: usize) -> Option<VecUV2f> {
+        self.texture_indices.get(corner).map(|&index| model.textures[index])
+    }
+
+    pub fn has_normals(&self) -> bool {
+        !self.normal_indices.is_empty()
+    }
+
+    pub fn normal(&self, model: &Model, corner: usize) -> Option<Vec3f> {
+        self.normal_indices.get(corner).map(|&index| model.normals[index])
+    }
+
+    // Color of the corner's vertex (0..1 per channel), if its `v` line had one.
+    pub fn color(&self, model: &Model, corner: usize) -> Option<Vec3f> {
+        model.colors.get(self.vertex_indices[corner]).copied().flatten()
+    }
+
+    pub fn bounding_box(&self, model: &Model) -> Aabb {
+        self.vertices(model).fold(Aabb::empty(), Aabb::extend)
+    }
+
+    pub fn area(&self, model: &Model) -> f64 {
+        self.weighted_normal(&model.vertices).norm() * 0.5
+    }
+
+    // Unnormalized face normal whose length is twice the face area.
+    fn weighted_normal(&self, vertices: &[Vec3f]) -> Vec3f {
+        if self.len() < 3 {
+            return Vec3f::new(0.0, 0.0, 0.0);
+        }
+
+        let origin = vertices[self.vertex_indices[0]];
+        self.vertex_indices[1..].windows(2)
+            .map(|edge| (vertices[edge[0]] - origin).cross(&(vertices[edge[1]] - origin)))
+            .fold(Vec3f::new(0.0, 0.0, 0.0), |sum, v| sum + v)
+    }
+
+    pub fn is_degenerate(&self, model: &Model) -> bool {
+        self.is_degenerate_in(&model.vertices)
+    }
+
+    fn is_degenerate_in(&self, vertices: &[Vec3f]) -> bool {
+        let has_duplicates = self.vertex_indices.iter()
+            .enumerate()
+            .any(|(i, &a)| self.vertex_indices[i + 1..].iter()
+                .map(|&b| (vertices[a], vertices[b]))
+                .any(|(v1, v2)| v1.x == v2.x && v1.y == v2.y && v1.z == v2.z));
+
+        has_duplicates || self.weighted_normal(vertices).norm() * 0.5 < f64::MIN_POSITIVE
     }
 }
 
 pub struct Model {
-    faces: Vec<Face>
+    faces: Vec<Face>,
+    vertices: Vec<Vec3f>,
+    textures: Vec<VecUV2f>,
+    normals: Vec<Vec3f>,
+    // per vertex, empty when no `v` line has a color
+    colors: Vec<Option<Vec3f>>,
+    lines: Vec<Vec<usize>>,
+    materials: Vec<Material>,
+    stats: ModelStats,
+    front_face: FrontFace,
+    adjacency: OnceCell<Adjacency>,
+    // name of each `g`/`o` group and the index of its first face
+    groups: Vec<(String, usize)>,
+    generation: u64,
+}
+
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
 }
 
 pub struct ModelIterator<'a> {
@@ -187,40 +436,509 @@ impl<'a> Iterator for ModelIterator<'a> {
 impl Model {
     pub fn from_file<P>(filename: P) -> Result<Model, RenderError>
         where P: AsRef<Path>, {
+        Model::parse(filename).map(|(model, _)| model)
+    }
+
+    // OBJ data from any reader; material libraries are looked up relative to
+    // `base_dir`.
+    pub fn from_reader<R: BufRead>(reader: R, base_dir: &Path) -> Result<Model, RenderError> {
+        Model::parse_reader(reader, base_dir).map(|(model, _)| model)
+    }
+
+    // One model per `o` object in the file, each with its own compacted
+    // vertex table. Faces before the first `o` form an object of their own.
+    // Polylines are not split and are left out.
+    pub fn load_all<P>(filename: P) -> Result<Vec<Model>, RenderError>
+        where P: AsRef<Path>, {
+        let (model, mut objects) = Model::parse(filename)?;
+        objects.insert(0, 0);
+        objects.push(model.faces.len());
+
+        Ok(objects.windows(2)
+            .filter(|range| range[0] < range[1])
+            .map(|range| model.submodel(model.faces[range[0]..range[1]].iter().collect()))
+            .collect())
+    }
 
-        let file = File::open(&filename)?;
-        let file = BufReader::new(file);
+    // Parses the whole file as a single model, also returning the index of
+    // the first face of each `o` object.
+    fn parse<P>(filename: P) -> Result<(Model, Vec<usize>), RenderError>
+        where P: AsRef<Path>, {
+        let base_dir = filename.as_ref().parent().unwrap_or(Path::new(""));
+        Model::parse_reader(open(filename.as_ref())?, base_dir)
+    }
 
+    fn parse_reader<R: BufRead>(file: R, base_dir: &Path) -> Result<(Model, Vec<usize>), RenderError> {
+        let mut materials = Vec::new();
         let mut vertices = Vec::new();
+        let mut colors = Vec::new();
         let mut normals = Vec::new();
         let mut textures = Vec::new();
         let mut faces = Vec::new();
+        let mut lines = Vec::new();
+        let mut smoothing_group = 0;
+        let mut material = None;
+        let mut objects = Vec::new();
+        let mut groups = vec![(String::new(), 0)];
 
         for (line, maybe_line) in file.lines().enumerate() {
-            if let Some((first, rest)) = maybe_line?.split_once(' ') {
+            let text = maybe_line?;
+            let at = |msg: String| format!("at line {}: {} in {:?}", line + 1, msg, text.trim());
+            if let Some((first, rest)) = split_statement(&text) {
                 match first {
-                    "v"  => parse_vec3f(rest)
-                        .map(|vertex| vertices.push(vertex))
-                        .map_err(|msg| RenderError::VertexParsingError(
-                            format!("at line {}: {}", line + 1, msg))),
+                    "v"  => parse_vertex(rest)
+                        .map(|(vertex, color)| {
+                            vertices.push(vertex);
+                            colors.push(color);
+                        })
+                        .map_err(|msg| RenderError::VertexParsingError(at(msg))),
                     "vn" => parse_vec3f(rest)
-                        .map(|normal| normals.push(normal.normalize()))
-                        .map_err(|msg| RenderError::NormalParsingError(
-                            format!("at line {}: {}", line + 1, msg))),
+                        .and_then(|normal| normal.try_normalize().ok_or("zero length normal".to_string()))
+                        .map(|normal| normals.push(normal))
+                        .map_err(|msg| RenderError::NormalParsingError(at(msg))),
                     "vt" => parse_vec_uv_2f(rest)
                         .map(|texture| textures.push(texture))
-                        .map_err(|msg| RenderError::TextureParsingError(
-                            format!("at line {}: {}", line + 1, msg))),
-                    "f"  => Face::from(rest, &vertices, &textures, &normals)
-                        .map(|face| faces.push(face))
-                        .map_err(|msg| RenderError::FaceParsingError(
-                            format!("at line {}: {}", line + 1, msg))),
+                        .map_err(|msg| RenderError::TextureParsingError(at(msg))),
+                    "f"  => Face::from(rest, vertices.len(), textures.len(), normals.len())
+                        .map(|face| faces.push(Face { smoothing_group, material, ..face }))
+                        .map_err(|msg| RenderError::FaceParsingError(at(msg))),
+                    "s"  => parse_smoothing_group(rest)
+                        .map(|group| smoothing_group = group)
+                        .map_err(|msg| RenderError::SmoothingGroupParsingError(at(msg))),
+                    "l"  => parse_polyline(rest, &vertices)
+                        .map(|polyline| lines.push(polyline))
+                        .map_err(|msg| RenderError::LineParsingError(at(msg))),
+                    "o"  => {
+                        objects.push(faces.len());
+                        groups.push((rest.trim().to_string(), faces.len()));
+                        Ok(())
+                    }
+                    "g"  => {
+                        groups.push((rest.trim().to_string(), faces.len()));
+                        Ok(())
+                    }
+                    // names missing from the libraries leave faces without
+                    // a material
+                    "usemtl" => {
+                        material = materials.iter()
+                            .rposition(|m: &Material| m.name == rest.trim());
+                        Ok(())
+                    }
+                    "mtllib" => rest
+                        .split_whitespace()
+                        .try_for_each(|library| {
+                            Material::from_file(base_dir.join(library), base_dir)
+                                .map(|library| materials.extend(library))
+                        }),
                     _ => Ok(())
                 }?
             }
         }
 
-        Ok(Model{faces})
+        let stats = ModelStats {
+            vertices: vertices.len(),
+            normals: normals.len(),
+            textures: textures.len(),
+            faces: faces.len(),
+        };
+
+        if colors.iter().all(Option::is_none) {
+            colors.clear();
+        }
+
+        let model = Model {
+            faces,
+            vertices,
+            textures,
+            normals,
+            colors,
+            lines,
+            materials,
+            stats,
+            front_face: FrontFace::default(),
+            adjacency: OnceCell::new(),
+            groups,
+            generation: next_generation(),
+        };
+        Ok((model, objects))
+    }
+
+    pub fn from_parts(vertices: Vec<Vec3f>,
+                      textures: Vec<VecUV2f>,
+                      normals: Vec<Vec3f>,
+                      faces: Vec<Vec<usize>>) -> Model {
+        let stats = ModelStats {
+            vertices: vertices.len(),
+            normals: normals.len(),
+            textures: textures.len(),
+            faces: faces.len(),
+        };
+
+        // textures and normals are parallel to the vertices
+        let faces = faces.into_iter()
+            .map(|indices| Face {
+                texture_indices: if indices.iter().all(|&i| i < textures.len()) {
+                    indices.clone()
+                } else {
+                    Vec::new()
+                },
+                normal_indices: if indices.iter().all(|&i| i < normals.len()) {
+                    indices.clone()
+                } else {
+                    Vec::new()
+                },
+                vertex_indices: indices,
+                smoothing_group: 0,
+                material: None,
+            })
+            .collect();
+
+        Model {
+            faces,
+            vertices,
+            textures,
+            normals,
+            colors: Vec::new(),
+            lines: Vec::new(),
+            materials: Vec::new(),
+            stats,
+            front_face: FrontFace::default(),
+            adjacency: OnceCell::new(),
+            groups: vec![(String::new(), 0)],
+            generation: next_generation(),
+        }
+    }
+
+    pub fn peek_counts<P>(filename: P) -> Result<ModelStats, RenderError>
+        where P: AsRef<Path>, {
+
+        let file = open(filename.as_ref())?;
+
+        let mut stats = ModelStats::default();
+
+        for maybe_line in file.lines() {
+            if let Some((first, _)) = split_statement(&maybe_line?) {
+                match first {
+                    "v"  => stats.vertices += 1,
+                    "vn" => stats.normals += 1,
+                    "vt" => stats.textures += 1,
+                    "f"  => stats.faces += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+
+    pub fn remove_degenerate_faces(&mut self) -> usize {
+        let count = self.faces.len();
+        let mut kept_before = vec![0];
+        for face in &self.faces {
+            kept_before.push(kept_before[kept_before.len() - 1] + !face.is_degenerate(self) as usize);
+        }
+        for (_, start) in self.groups.iter_mut() {
+            *start = kept_before[*start];
+        }
+        let vertices = &self.vertices;
+        self.faces.retain(|face| !face.is_degenerate_in(vertices));
+
+        let removed = count - self.faces.len();
+        self.stats.faces -= removed;
+        self.changed();
+        removed
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        Aabb::from_points(&self.vertices)
+    }
+
+    // Middle of the bounding box, `None` for a model without vertices.
+    pub fn center(&self) -> Option<Vec3f> {
+        let bounds = self.bounding_box();
+        (!bounds.is_empty()).then(|| bounds.center())
+    }
+
+    // Centers the model on the origin and scales it uniformly so that its
+    // longest side is 1, i.e. it fits in [-0.5, 0.5] on every axis. Normals
+    // are unaffected by a uniform scale.
+    pub fn normalize_to_unit_cube(&mut self) {
+        let bounds = self.bounding_box();
+        if bounds.is_empty() {
+            return;
+        }
+        let center = bounds.center();
+        let size = bounds.size();
+        let longest = size.x.max(size.y).max(size.z);
+        let scale = if longest > 0.0 { 1.0 / longest } else { 1.0 };
+
+        self.vertices.iter_mut().for_each(|v| *v = (*v - center) * scale);
+        self.changed();
+    }
+
+    // Rotation whose rows are the principal axes of the vertex cloud, ordered by
+    // decreasing variance: applying it maps the dominant axis onto x.
+    pub fn principal_axes(&self) -> Mat3x3f {
+        if self.vertices.is_empty() {
+            return Mat3x3f::identity();
+        }
+
+        let count = self.vertices.len() as f64;
+        let centroid = self.vertices.iter()
+            .fold(Vec3f::new(0.0, 0.0, 0.0), |sum, &v| sum + v) / count;
+
+        let mut covariance = Mat3x3f::new();
+        for &vertex in &self.vertices {
+            let d = vertex - centroid;
+            let d = [d.x, d.y, d.z];
+            for row in 0..3 {
+                for col in 0..3 {
+                    covariance[row][col] += d[row] * d[col] / count;
+                }
+            }
+        }
+
+        let (values, vectors) = covariance.symmetric_eigen();
+        let values = [values.x, values.y, values.z];
+        let mut order = [0, 1, 2];
+        order.sort_by(|&a, &b| values[b].total_cmp(&values[a]));
+
+        let mut rotation = Mat3x3f::new();
+        for (row, &axis) in order.iter().enumerate() {
+            for col in 0..3 {
+                rotation[row][col] = vectors[col][axis];
+            }
+        }
+
+        // keep it a proper rotation rather than a reflection
+        if rotation.det() < 0.0 {
+            for col in 0..3 {
+                rotation[2][col] = -rotation[2][col];
+            }
+        }
+        rotation
+    }
+
+    // Replaces the normals: faces sharing a smoothing group average their
+    // (area weighted) normals at shared vertex positions, faces with smoothing
+    // off get their flat face normal.
+    pub fn generate_normals(&mut self) {
+        self.smooth_normals(|face| (face.smoothing_group != 0).then_some(face.smoothing_group));
+    }
+
+    // Replaces the normals with smooth ones across the whole model, ignoring
+    // smoothing groups: the area weighted normals of all faces meeting at a
+    // vertex position are summed into one normal per position, so corners
+    // repeated in the vertex table still end up with a single normal.
+    pub fn compute_normals(&mut self) {
+        self.smooth_normals(|_| Some(0));
+    }
+
+    // `group` picks the faces that are averaged together, `None` shades a
+    // face flat. The normal table is rebuilt with one entry per smoothed
+    // position and one per flat face.
+    fn smooth_normals(&mut self, group: impl Fn(&Face) -> Option<u32>) {
+        let vertices = &self.vertices;
+        let key = |group: u32, index: usize| (group, vertex_key(&vertices[index]));
+
+        let mut slots: HashMap<(u32, (u64, u64, u64)), usize> = HashMap::new();
+        let mut normals = Vec::new();
+        for face in self.faces.iter() {
+            let Some(group) = group(face) else { continue };
+            let normal = face.weighted_normal(vertices);
+            for &index in &face.vertex_indices {
+                let slot = *slots.entry(key(group, index)).or_insert_with(|| {
+                    normals.push(Vec3f::new(0.0, 0.0, 0.0));
+                    normals.len() - 1
+                });
+                normals[slot] += normal;
+            }
+        }
+        // zero-area faces have no direction, their normal stays zero rather
+        // than NaN and leaves them unlit
+        let unit = |normal: Vec3f| normal.try_normalize().unwrap_or(Vec3f::new(0.0, 0.0, 0.0));
+        normals.iter_mut().for_each(|normal| *normal = unit(*normal));
+
+        for face in self.faces.iter_mut() {
+            face.normal_indices = match group(face) {
+                None => {
+                    normals.push(unit(face.weighted_normal(vertices)));
+                    vec![normals.len() - 1; face.len()]
+                }
+                Some(group) => face.vertex_indices.iter()
+                    .map(|&index| slots[&key(group, index)])
+                    .collect(),
+            };
+        }
+        self.normals = normals;
+        self.stats.normals = self.normals.len();
+        self.changed();
+    }
+
+    // Splits the model into groups of faces connected through shared vertices.
+    pub fn connected_components(&self) -> Vec<Model> {
+        let mut ids = HashMap::new();
+        let face_ids: Vec<Vec<usize>> = self.faces.iter()
+            .map(|face| face.vertices(self)
+                .map(|v| {
+                    let next = ids.len();
+                    *ids.entry(vertex_key(&v)).or_insert(next)
+                })
+                .collect())
+            .collect();
+
+        let mut parent: Vec<usize> = (0..ids.len()).collect();
+        for indices in &face_ids {
+            for &index in indices.iter().skip(1) {
+                let a = find_root(&mut parent, indices[0]);
+                let b = find_root(&mut parent, index);
+                parent[b] = a;
+            }
+        }
+
+        let mut components: Vec<Vec<&Face>> = Vec::new();
+        let mut component_of_root = HashMap::new();
+        for (face, indices) in self.faces.iter().zip(&face_ids) {
+            if let Some(&first) = indices.first() {
+                let root = find_root(&mut parent, first);
+                let component = *component_of_root.entry(root).or_insert_with(|| {
+                    components.push(Vec::new());
+                    components.len() - 1
+                });
+                components[component].push(face);
+            }
+        }
+
+        components.into_iter().map(|faces| self.submodel(faces)).collect()
+    }
+
+    // New model made of a subset of this model's faces, with its own compacted
+    // vertex, texture and normal tables.
+    fn submodel(&self, faces: Vec<&Face>) -> Model {
+        fn compact<T: Copy>(indices: &[usize], table: &[T], remap: &mut HashMap<usize, usize>,
+                            compacted: &mut Vec<T>) -> Vec<usize> {
+            indices.iter()
+                .map(|&index| *remap.entry(index).or_insert_with(|| {
+                    compacted.push(table[index]);
+                    compacted.len() - 1
+                }))
+                .collect()
+        }
+
+        let (mut vertex_remap, mut texture_remap, mut normal_remap) =
+            (HashMap::new(), HashMap::new(), HashMap::new());
+        let (mut vertices, mut textures, mut normals) = (Vec::new(), Vec::new(), Vec::new());
+        let faces: Vec<Face> = faces.into_iter()
+            .map(|face| Face {
+                vertex_indices: compact(&face.vertex_indices, &self.vertices, &mut vertex_remap,
+                                        &mut vertices),
+                texture_indices: compact(&face.texture_indices, &self.textures, &mut texture_remap,
+                                         &mut textures),
+                normal_indices: compact(&face.normal_indices, &self.normals, &mut normal_remap,
+                                        &mut normals),
+                ..face.clone()
+            })
+            .collect();
+
+        let mut colors = vec![None; if self.colors.is_empty() { 0 } else { vertices.len() }];
+        for (&old, &new) in &vertex_remap {
+            if let Some(color) = colors.get_mut(new) {
+                *color = self.colors[old];
+            }
+        }
+
+        let stats = ModelStats {
+            vertices: vertices.len(),
+            normals: normals.len(),
+            textures: textures.len(),
+            faces: faces.len(),
+        };
+        Model {
+            faces,
+            vertices,
+            textures,
+            normals,
+            colors,
+            lines: Vec::new(),
+            materials: self.materials.clone(),
+            stats,
+            front_face: self.front_face,
+            adjacency: OnceCell::new(),
+            groups: vec![(String::new(), 0)],
+            generation: next_generation(),
+        }
+    }
+
+    // Different for every model and bumped whenever one is edited in place,
+    // so caches of derived data can tell when they are stale.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn changed(&mut self) {
+        self.adjacency = OnceCell::new();
+        self.generation = next_generation();
+    }
+
+    // Built on first use and cached until the faces change.
+    pub fn adjacency(&self) -> &Adjacency {
+        self.adjacency.get_or_init(|| self.build_adjacency())
+    }
+
+    pub fn build_adjacency(&self) -> Adjacency {
+        Adjacency::new(self.vertices.len(), self.faces.iter().map(|face| face.vertex_indices.as_slice()))
+    }
+
+    // True when there is nothing to fill, e.g. an OBJ with only vertices.
+    pub fn is_empty(&self) -> bool {
+        self.faces.is_empty()
+    }
+
+    pub fn front_face(&self) -> FrontFace {
+        self.front_face
+    }
+
+    // For meshes wound the other way round; the faces stay untouched.
+    pub fn set_front_face(&mut self, front_face: FrontFace) {
+        self.front_face = front_face;
+    }
+
+    pub fn vertices(&self) -> &[Vec3f] {
+        &self.vertices
+    }
+
+    pub fn textures(&self) -> &[VecUV2f] {
+        &self.textures
+    }
+
+    pub fn normals(&self) -> &[Vec3f] {
+        &self.normals
+    }
+
+    pub fn lines(&self) -> &[Vec<usize>] {
+        &self.lines
+    }
+
+    pub fn materials(&self) -> &[Material] {
+        &self.materials
+    }
+
+    // Faces by the `g` or `o` statement they follow, in file order; faces
+    // before the first one are in the group "". Groups without faces are
+    // left out, and a name used again later shows up once per use.
+    pub fn groups(&self) -> impl Iterator<Item = (&str, &[Face])> {
+        let ends = self.groups.iter()
+            .skip(1)
+            .map(|&(_, start)| start)
+            .chain(std::iter::once(self.faces.len()));
+        self.groups.iter()
+            .zip(ends)
+            .filter(|&(&(_, start), end)| start < end)
+            .map(|((name, start), end)| (name.as_str(), &self.faces[*start..end]))
+    }
+
+    pub fn stats(&self) -> ModelStats {
+        self.stats
     }
 
     pub fn iter(&self) -> ModelIterator {
@@ -229,4 +947,13 @@ impl Model {
             index: 0,
         }
     }
+
+    // Model-space triangles of every face, fan-triangulating polygons.
+    pub fn triangles(&self) -> impl Iterator<Item = Triangle> + '_ {
+        self.faces.iter().flat_map(move |face| {
+            (1..face.len().saturating_sub(1))
+                .map(move |i| Triangle::new(face.vertex(self, 0), face.vertex(self, i),
+                                            face.vertex(self, i + 1)))
+        })
+    }
 }
\ No newline at end of file
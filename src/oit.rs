@@ -0,0 +1,63 @@
+use crate::common::Resolution;
+use crate::framebuffer::{FrameBuffer, RenderTarget, Rgb};
+use crate::geometry::Vec3f;
+
+// Weighted blended order-independent transparency: the transparent pass sums
+// weighted colors and multiplies revealage per pixel, neither of which depends
+// on draw order, and `resolve` composites the result over the opaque frame.
+pub struct OitBuffer {
+    resolution: Resolution,
+    accumulation: Vec<(Vec3f, f64)>,
+    revealage: Vec<f64>,
+}
+
+impl OitBuffer {
+    pub fn new<R: Into<Resolution>>(resolution: R) -> Self {
+        let resolution = resolution.into();
+        let size = (resolution.width * resolution.height) as usize;
+        OitBuffer {
+            resolution,
+            accumulation: vec![(Vec3f::default(), 0.0); size],
+            revealage: vec![1.0; size],
+        }
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    pub fn clear(&mut self) {
+        self.accumulation.fill((Vec3f::default(), 0.0));
+        self.revealage.fill(1.0);
+    }
+
+    pub fn accumulate(&mut self, x: u32, y: u32, color: Vec3f, alpha: f64, weight: f64) {
+        if x >= self.resolution.width || y >= self.resolution.height {
+            return;
+        }
+        let index = (x + self.resolution.width * y) as usize;
+        let (sum, alpha_sum) = &mut self.accumulation[index];
+        *sum += color * (alpha * weight);
+        *alpha_sum += alpha * weight;
+        self.revealage[index] *= 1.0 - alpha;
+    }
+
+    pub fn resolve(&self, framebuffer: &mut FrameBuffer) -> Result<(), String> {
+        let width = self.resolution.width;
+        for (index, (&(sum, alpha_sum), &revealage)) in
+                self.accumulation.iter().zip(&self.revealage).enumerate() {
+            if revealage >= 1.0 {
+                continue;
+            }
+            let (x, y) = (index as u32 % width, index as u32 / width);
+            let background = match framebuffer.pixels().get(index) {
+                Some(&pixel) => Vec3f::from(pixel),
+                None => continue,
+            };
+            let average = sum / alpha_sum.max(1e-5);
+            let color = average * (1.0 - revealage) + background * revealage;
+            framebuffer.draw_pixel(x, y, Rgb::from(color))?;
+        }
+        Ok(())
+    }
+}
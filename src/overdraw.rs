@@ -0,0 +1,63 @@
+use crate::common::Resolution;
+use crate::framebuffer::{FrameBuffer, RenderTarget, Rgb};
+use crate::geometry::Vec3f;
+
+// Number of depth tests evaluated per pixel, passing or not, which is what
+// the rasterizer pays for regardless of what ends up visible.
+pub struct OverdrawBuffer {
+    resolution: Resolution,
+    counts: Vec<u32>,
+}
+
+impl OverdrawBuffer {
+    pub fn new<R: Into<Resolution>>(resolution: R) -> Self {
+        let resolution = resolution.into();
+        let size = (resolution.width * resolution.height) as usize;
+        OverdrawBuffer { resolution, counts: vec![0; size] }
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.fill(0);
+    }
+
+    pub fn record(&mut self, x: u32, y: u32) {
+        if x < self.resolution.width && y < self.resolution.height {
+            self.counts[(x + self.resolution.width * y) as usize] += 1;
+        }
+    }
+
+    pub fn count(&self, x: u32, y: u32) -> Option<u32> {
+        if x < self.resolution.width && y < self.resolution.height {
+            Some(self.counts[(x + self.resolution.width * y) as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn max(&self) -> u32 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    // Replaces the frame with a heat map, scaled so that the most overdrawn
+    // pixel is white; untouched pixels are black.
+    pub fn resolve(&self, framebuffer: &mut FrameBuffer) -> Result<(), String> {
+        let max = self.max().max(1) as f64;
+        let width = self.resolution.width;
+        for (index, &count) in self.counts.iter().enumerate() {
+            let (x, y) = (index as u32 % width, index as u32 / width);
+            framebuffer.draw_pixel(x, y, heat_color(count as f64 / max))?;
+        }
+        Ok(())
+    }
+}
+
+// Black through red and yellow to white, getting brighter all the way.
+fn heat_color(t: f64) -> Rgb {
+    let t = t.clamp(0.0, 1.0) * 3.0;
+    let color = Vec3f::new(t.min(1.0), (t - 1.0).clamp(0.0, 1.0), (t - 2.0).clamp(0.0, 1.0));
+    Rgb::from(color * 255.0)
+}
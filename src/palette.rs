@@ -0,0 +1,33 @@
+use crate::geometry::Vec3f;
+
+const GOLDEN_RATIO_CONJUGATE: f64 = 0.618_033_988_749_895;
+
+const SATURATION: f64 = 0.65;
+const VALUE: f64 = 0.95;
+
+pub fn distinct_hue(index: usize) -> f64 {
+    (index as f64 * GOLDEN_RATIO_CONJUGATE).fract()
+}
+
+pub fn distinct_color(index: usize) -> Vec3f {
+    hsv_to_rgb(distinct_hue(index), SATURATION, VALUE)
+}
+
+// All of hue, saturation and value are in [0, 1]; the result is in [0, 255].
+pub fn hsv_to_rgb(hue: f64, saturation: f64, value: f64) -> Vec3f {
+    let h = hue.rem_euclid(1.0) * 6.0;
+    let c = value * saturation;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let m = value - c;
+
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    Vec3f::new(r + m, g + m, b + m) * 255.0
+}
@@ -0,0 +1,115 @@
+use std::f64::consts::PI;
+
+use crate::geometry::{Vec3f, VecUV2f};
+use crate::model::Model;
+
+impl Model {
+    pub fn cube(size: f64) -> Model {
+        let half = size * 0.5;
+        let sides = [
+            (Vec3f::new( 1.0,  0.0,  0.0), Vec3f::new(0.0, 1.0, 0.0)),
+            (Vec3f::new(-1.0,  0.0,  0.0), Vec3f::new(0.0, 1.0, 0.0)),
+            (Vec3f::new( 0.0,  1.0,  0.0), Vec3f::new(0.0, 0.0, 1.0)),
+            (Vec3f::new( 0.0, -1.0,  0.0), Vec3f::new(0.0, 0.0, 1.0)),
+            (Vec3f::new( 0.0,  0.0,  1.0), Vec3f::new(1.0, 0.0, 0.0)),
+            (Vec3f::new( 0.0,  0.0, -1.0), Vec3f::new(1.0, 0.0, 0.0)),
+        ];
+
+        let mut vertices = Vec::new();
+        let mut textures = Vec::new();
+        let mut normals = Vec::new();
+        let mut faces = Vec::new();
+
+        for (normal, up) in sides {
+            let right = up.cross(&normal);
+            let base = vertices.len();
+            for (u, v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                let position = normal + right * (2.0 * u - 1.0) + up * (2.0 * v - 1.0);
+                vertices.push(position * half);
+                textures.push(VecUV2f::new(u, v));
+                normals.push(normal);
+            }
+            faces.push(vec![base, base + 1, base + 2]);
+            faces.push(vec![base, base + 2, base + 3]);
+        }
+
+        Model::from_parts(vertices, textures, normals, faces)
+    }
+
+    pub fn uv_sphere(radius: f64, segments: usize, rings: usize) -> Model {
+        let segments = segments.max(3);
+        let rings = rings.max(2);
+
+        let mut vertices = Vec::new();
+        let mut textures = Vec::new();
+        let mut normals = Vec::new();
+        let mut faces = Vec::new();
+
+        for ring in 0..=rings {
+            let v = ring as f64 / rings as f64;
+            let theta = PI * v;
+            for segment in 0..=segments {
+                let u = segment as f64 / segments as f64;
+                let phi = 2.0 * PI * u;
+                let normal = Vec3f::new(theta.sin() * phi.cos(),
+                                        theta.cos(),
+                                        -theta.sin() * phi.sin());
+                vertices.push(normal * radius);
+                textures.push(VecUV2f::new(u, 1.0 - v));
+                normals.push(normal);
+            }
+        }
+
+        let index = |ring: usize, segment: usize| ring * (segments + 1) + segment;
+        for ring in 0..rings {
+            for segment in 0..segments {
+                let a = index(ring, segment);
+                let b = index(ring + 1, segment);
+                let c = index(ring + 1, segment + 1);
+                let d = index(ring, segment + 1);
+                if ring != 0 {
+                    faces.push(vec![a, b, d]);
+                }
+                if ring != rings - 1 {
+                    faces.push(vec![b, c, d]);
+                }
+            }
+        }
+
+        Model::from_parts(vertices, textures, normals, faces)
+    }
+
+    // The plane lies in the XY plane facing +Z, towards the default camera.
+    pub fn plane(width: f64, height: f64, subdivisions: usize) -> Model {
+        let cells = subdivisions + 1;
+
+        let mut vertices = Vec::new();
+        let mut textures = Vec::new();
+        let mut normals = Vec::new();
+        let mut faces = Vec::new();
+
+        for row in 0..=cells {
+            let v = row as f64 / cells as f64;
+            for col in 0..=cells {
+                let u = col as f64 / cells as f64;
+                vertices.push(Vec3f::new((u - 0.5) * width, (v - 0.5) * height, 0.0));
+                textures.push(VecUV2f::new(u, v));
+                normals.push(Vec3f::new(0.0, 0.0, 1.0));
+            }
+        }
+
+        let index = |row: usize, col: usize| row * (cells + 1) + col;
+        for row in 0..cells {
+            for col in 0..cells {
+                let a = index(row, col);
+                let b = index(row, col + 1);
+                let c = index(row + 1, col + 1);
+                let d = index(row + 1, col);
+                faces.push(vec![a, b, c]);
+                faces.push(vec![a, c, d]);
+            }
+        }
+
+        Model::from_parts(vertices, textures, normals, faces)
+    }
+}
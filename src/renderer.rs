@@ -1,157 +1,1242 @@
-use sdl2::pixels::Color;
-use sdl2::render::WindowCanvas;
-use sdl2::rect::Point;
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
 
-use crate::common::Resolution;
-use crate::geometry::{Mat4x1f, Mat4x4f, Triangle, Vec3f};
-use crate::model::Face;
+use crate::common::{Point, Resolution};
+use crate::font::{glyph, GLYPH_ADVANCE, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::framebuffer::{FrameBuffer, RenderTarget, Rgb};
+use crate::geometry::{Aabb, Mat3x3f, Mat4x1f, Mat4x4f, SqMatrix, Triangle, Vec2f, Vec3f, VecUV2f};
+use crate::model::{Face, FrontFace, Model};
+use crate::oit::OitBuffer;
+use crate::overdraw::OverdrawBuffer;
+use crate::palette::distinct_color;
+use crate::shader::{Shader, Varyings};
+use crate::shadow::{Light, ShadowMap};
+use crate::texture::Texture;
+use crate::zbuffer::{MultisampleZBuffer, ZBuffer};
 
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ShadingMode {
+    #[default]
+    Gouraud,
+    FaceId,
+    NormalRgb,
+}
+
+impl ShadingMode {
+    pub const ALL: [ShadingMode; 3] = [ShadingMode::Gouraud, ShadingMode::FaceId, ShadingMode::NormalRgb];
+
+    // The mode after this one in `ALL`, wrapping around to the first.
+    pub fn next(self) -> ShadingMode {
+        let index = ShadingMode::ALL.iter().position(|&mode| mode == self).unwrap();
+        ShadingMode::ALL[(index + 1) % ShadingMode::ALL.len()]
+    }
+}
+
+// What `render_face` does with faces that come without vertex normals.
+#[derive(Copy, Clone, Debug, Default)]
+pub enum MissingNormalPolicy {
+    // red, green and blue corners, handy for spotting such faces
+    Rainbow,
+    // lit with the normal of the face plane
+    #[default]
+    FlatGeometric,
+    // unlit, in the given color (0..255 per channel)
+    SolidColor(Vec3f),
+}
+
+// How the cosine between light and normal turns into diffuse intensity.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DiffuseModel {
+    #[default]
+    Lambert,
+    // (dot * 0.5 + 0.5)^2, wraps light past the terminator for a softer look
+    HalfLambert,
+}
+
+impl DiffuseModel {
+    pub fn intensity(&self, dot: f64) -> f64 {
+        match self {
+            DiffuseModel::Lambert => dot,
+            DiffuseModel::HalfLambert => (dot * 0.5 + 0.5).powi(2),
+        }
+    }
+
+    // Whether faces turned away from the light (dot <= 0) still get some.
+    pub fn wraps(&self) -> bool {
+        *self == DiffuseModel::HalfLambert
+    }
+}
+
+// Which faces are dropped by their screen-space winding, see `FrontFace`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CullMode {
+    #[default]
+    None,
+    Back,
+    Front,
+}
+
+impl CullMode {
+    fn culls(&self, triangle: &Triangle, front_face: FrontFace) -> bool {
+        let [p1, p2, p3] = triangle.vertices().map(Vec2f::from);
+        let counter_clockwise = (p2 - p1).cross(&(p3 - p1)) > 0.0;
+        let front = counter_clockwise == (front_face == FrontFace::CounterClockwise);
+        match self {
+            CullMode::None => false,
+            CullMode::Back => !front,
+            CullMode::Front => front,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DepthFunc {
+    #[default]
+    Greater,
+    Less,
+}
+
+impl DepthFunc {
+    pub fn clear_value(&self) -> f64 {
+        match self {
+            DepthFunc::Greater => f64::MIN,
+            DepthFunc::Less => f64::MAX,
+        }
+    }
+
+    pub fn offset(&self, depth: f64, offset: f64) -> f64 {
+        match self {
+            DepthFunc::Greater => depth + offset,
+            DepthFunc::Less => depth - offset,
+        }
+    }
+
+    pub fn passes(&self, depth: f64, stored: f64) -> bool {
+        match self {
+            DepthFunc::Greater => depth > stored,
+            DepthFunc::Less => depth < stored,
+        }
+    }
+}
+
+// How `Renderer::resolve_depth` reduces the covered samples of a pixel to one
+// depth. `Nearest` always yields the depth of a surface that was actually
+// drawn, so unprojecting a picked pixel lands on geometry, but silhouette
+// pixels snap wholly to the foreground. `Average` gives smoother depths along
+// edges for depth-tested overlays, but where two surfaces share a pixel the
+// result lies between them on neither, and picking there returns a point
+// floating in space.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DepthResolve {
+    #[default]
+    Nearest,
+    Average,
+}
+
+// Walks the Bresenham line from p0 to p1, passing each pixel together with
+// its parameter along the line (0 at p0, 1 at p1) to `plot`.
+fn trace_line(p0: Point,
+              p1: Point,
+              mut plot: impl FnMut(i32, i32, f64) -> Result<(), String>) -> Result<(), String> {
+    let (p0, p1, steep) = if (p0.x - p1.x).abs() < (p0.y - p1.y).abs() {
+        (Point::new(p0.y, p0.x), Point::new(p1.y, p1.x), true)
+    } else {
+        (p0, p1, false)
+    };
+
+    let (p0, p1, reversed) = if p0.x > p1.x {
+        (p1, p0, true)
+    } else {
+        (p0, p1, false)
+    };
+
+    let dx = p1.x - p0.x;
+    let dy = p1.y - p0.y;
+
+    let iy = if p1.y > p0.y { 1 } else { -1 };
+
+    let derr2 = dy.abs() * 2;
+    let mut err2 = 0;
+
+    let mut y = p0.y;
+    for x in p0.x..=p1.x {
+        let t = if dx == 0 { 0.0 } else { (x - p0.x) as f64 / dx as f64 };
+        let t = if reversed { 1.0 - t } else { t };
+        if steep {
+            plot(y, x, t)?;
+        } else {
+            plot(x, y, t)?;
+        }
+        err2 += derr2;
+        if err2 > dx {
+            y += iy;
+            err2 -= dx * 2;
+        }
+    }
+    Ok(())
+}
+
+const INSIDE: u8 = 0;
+const LEFT: u8 = 1;
+const RIGHT: u8 = 2;
+const BOTTOM: u8 = 4;
+const TOP: u8 = 8;
+
+// Cohen-Sutherland clipping of a line to the [0, width) x [0, height) pixel
+// rectangle. Returns `None` when the line lies entirely outside.
+fn clip_line(p0: Point, p1: Point, width: u32, height: u32) -> Option<(Point, Point)> {
+    let (x_max, y_max) = (width as f64 - 1.0, height as f64 - 1.0);
+    let outcode = |x: f64, y: f64| {
+        let mut code = INSIDE;
+        if x < 0.0 { code |= LEFT } else if x > x_max { code |= RIGHT }
+        if y < 0.0 { code |= TOP } else if y > y_max { code |= BOTTOM }
+        code
+    };
+
+    let (mut x0, mut y0) = (p0.x as f64, p0.y as f64);
+    let (mut x1, mut y1) = (p1.x as f64, p1.y as f64);
+    let mut code0 = outcode(x0, y0);
+    let mut code1 = outcode(x1, y1);
+
+    loop {
+        if code0 | code1 == INSIDE {
+            let round = |x: f64, y: f64| Point::new(x.round() as i32, y.round() as i32);
+            return Some((round(x0, y0), round(x1, y1)));
+        }
+        if code0 & code1 != INSIDE {
+            return None;
+        }
+
+        let code = if code0 != INSIDE { code0 } else { code1 };
+        let (x, y) = if code & TOP != 0 {
+            (x0 + (x1 - x0) * (0.0 - y0) / (y1 - y0), 0.0)
+        } else if code & BOTTOM != 0 {
+            (x0 + (x1 - x0) * (y_max - y0) / (y1 - y0), y_max)
+        } else if code & RIGHT != 0 {
+            (x_max, y0 + (y1 - y0) * (x_max - x0) / (x1 - x0))
+        } else {
+            (0.0, y0 + (y1 - y0) * (0.0 - x0) / (x1 - x0))
+        };
+
+        if code == code0 {
+            (x0, y0) = (x, y);
+            code0 = outcode(x0, y0);
+        } else {
+            (x1, y1) = (x, y);
+            code1 = outcode(x1, y1);
+        }
+    }
+}
+
+fn vertex_color(varyings: &Varyings) -> Rgb {
+    Rgb::from(varyings.color)
+}
+
+// Maps the interpolated normal from [-1, 1] to [0, 255] per channel.
+fn normal_color(varyings: &Varyings) -> Rgb {
+    let normal = varyings.normal;
+    let normal = if normal.norm() > 0.0 { normal.normalize() } else { normal };
+    let channel = |n: f64| ((n * 0.5 + 0.5) * 255.0).round().clamp(0.0, 255.0) as u8;
+    Rgb::new(channel(normal.x), channel(normal.y), channel(normal.z))
+}
+
+// Vertex indices of the triangles of a strip of `count` vertices. Every
+// other triangle is flipped so all of them keep the winding of the first.
+pub fn triangle_strip(count: usize) -> impl Iterator<Item = [usize; 3]> {
+    (0..count.saturating_sub(2)).map(|i| if i % 2 == 0 { [i, i + 1, i + 2] } else { [i + 1, i, i + 2] })
+}
+
+// Vertex indices of the triangles of a fan of `count` vertices around the
+// first one.
+pub fn triangle_fan(count: usize) -> impl Iterator<Item = [usize; 3]> {
+    (1..count.saturating_sub(1)).map(|i| [0, i, i + 1])
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RenderTimings {
+    pub transform: Duration,
+    pub cull: Duration,
+    pub rasterize: Duration,
+    pub present: Duration,
+    pub total: Duration,
+    pub faces: usize,
+    pub culled: usize,
+    pub skipped: usize,
+    pub cached: usize,
+    pub flops: usize,
+}
+
+impl RenderTimings {
+    pub fn stages(&self) -> Duration {
+        self.transform + self.cull + self.rasterize + self.present
+    }
+}
+
+impl Display for RenderTimings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        write!(f, "transform {:.3} ms, cull {:.3} ms, rasterize {:.3} ms, present {:.3} ms, \
+                   total {:.3} ms ({} faces, {} culled, {} skipped, {} cached, {} transform flops)",
+               ms(self.transform), ms(self.cull), ms(self.rasterize), ms(self.present),
+               ms(self.total), self.faces, self.culled, self.skipped, self.cached, self.flops)
+    }
+}
+
+// Screen-space triangle of a face and the 1/w of its corners, `None` for
+// faces that can't be drawn.
+type ProjectedFace = Option<(Triangle, [f64; 3])>;
+
+// Projected faces of the last model rendered, valid as long as `key`
+// matches.
 #[derive(Default)]
+struct FaceCache {
+    key: u64,
+    faces: Vec<Option<ProjectedFace>>,
+}
+
+impl FaceCache {
+    fn validate(&mut self, key: u64, count: usize) {
+        if self.key != key || self.faces.len() != count {
+            self.key = key;
+            self.faces.clear();
+            self.faces.resize(count, None);
+        }
+    }
+}
+
+// Identifies a model, as of its latest edit, drawn with a given view port and
+// projection.
+fn face_cache_key(model: &Model, view_port: Mat4x4f, projection: Mat4x4f) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    model.generation().hash(&mut hasher);
+    for matrix in [view_port, projection] {
+        for row in 0..4 {
+            matrix[row].iter().for_each(|value| value.to_bits().hash(&mut hasher));
+        }
+    }
+    hasher.finish()
+}
+
+const DEFAULT_POLYGON_OFFSET: f64 = 1e-2;
+const SHADOW_BIAS: f64 = 1e-3;
+
+// multiplies and adds for a 4x4 * 4x4 and a 4x4 * 4x1 product
+const MAT4_MUL_FLOPS: usize = 112;
+const MAT4_VEC_FLOPS: usize = 28;
+
 pub struct Renderer {
     resolution: Resolution,
+    shading_mode: ShadingMode,
+    missing_normal_policy: MissingNormalPolicy,
+    diffuse_model: DiffuseModel,
+    cull_mode: CullMode,
+    depth_func: DepthFunc,
+    depth_resolve: DepthResolve,
+    polygon_offset: f64,
+    shadow_pcf: usize,
+    profiling: bool,
+    timings: Cell<RenderTimings>,
+    triangle_budget: Option<usize>,
+    budget_start: Cell<usize>,
+    face_cache: Option<RefCell<FaceCache>>,
+    line_depth_fade: Option<(f64, f64)>,
+    overdraw: Option<RefCell<OverdrawBuffer>>,
+    // diffuse textures by material index
+    textures: HashMap<usize, Texture>,
+    default_texture: Option<Texture>,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Renderer {
+            resolution: Default::default(),
+            shading_mode: Default::default(),
+            missing_normal_policy: Default::default(),
+            diffuse_model: Default::default(),
+            cull_mode: Default::default(),
+            depth_func: Default::default(),
+            depth_resolve: Default::default(),
+            polygon_offset: DEFAULT_POLYGON_OFFSET,
+            shadow_pcf: 1,
+            profiling: false,
+            timings: Default::default(),
+            triangle_budget: None,
+            budget_start: Cell::new(0),
+            face_cache: None,
+            line_depth_fade: None,
+            overdraw: None,
+            textures: HashMap::new(),
+            default_texture: None,
+        }
+    }
 }
 
 impl Renderer {
     pub fn new<R: Into<Resolution>> (resolution: R) -> Self {
         let resolution = resolution.into();
-        Renderer { resolution }
+        Renderer { resolution, ..Default::default() }
     }
 
-    pub fn render_line(&self, canvas: &mut WindowCanvas, p0: Point, p1: Point) -> Result<(), String> {
-        let (p0, p1, steep) = if (p0.x - p1.x).abs() < (p0.y - p1.y).abs() {
-            (Point::new(p0.y, p0.x), Point::new(p1.y, p1.x), true)
-        } else {
-            (p0, p1, false)
+    pub fn shading_mode(&self) -> ShadingMode {
+        self.shading_mode
+    }
+
+    pub fn set_shading_mode(&mut self, shading_mode: ShadingMode) {
+        self.shading_mode = shading_mode;
+    }
+
+    pub fn missing_normal_policy(&self) -> MissingNormalPolicy {
+        self.missing_normal_policy
+    }
+
+    pub fn set_missing_normal_policy(&mut self, policy: MissingNormalPolicy) {
+        self.missing_normal_policy = policy;
+    }
+
+    pub fn diffuse_model(&self) -> DiffuseModel {
+        self.diffuse_model
+    }
+
+    pub fn set_diffuse_model(&mut self, diffuse_model: DiffuseModel) {
+        self.diffuse_model = diffuse_model;
+    }
+
+    pub fn cull_mode(&self) -> CullMode {
+        self.cull_mode
+    }
+
+    pub fn set_cull_mode(&mut self, cull_mode: CullMode) {
+        self.cull_mode = cull_mode;
+    }
+
+    pub fn depth_func(&self) -> DepthFunc {
+        self.depth_func
+    }
+
+    pub fn set_depth_func(&mut self, depth_func: DepthFunc) {
+        self.depth_func = depth_func;
+    }
+
+    pub fn depth_clear_value(&self) -> f64 {
+        self.depth_func.clear_value()
+    }
+
+    pub fn depth_resolve(&self) -> DepthResolve {
+        self.depth_resolve
+    }
+
+    pub fn set_depth_resolve(&mut self, depth_resolve: DepthResolve) {
+        self.depth_resolve = depth_resolve;
+    }
+
+    pub fn polygon_offset(&self) -> f64 {
+        self.polygon_offset
+    }
+
+    pub fn set_polygon_offset(&mut self, polygon_offset: f64) {
+        self.polygon_offset = polygon_offset;
+    }
+
+    pub fn set_profiling(&mut self, profiling: bool) {
+        self.profiling = profiling;
+    }
+
+    // Stage timings of the last `render_model` call, only collected while
+    // profiling is enabled. `present` is left for the caller to fill in.
+    pub fn timings(&self) -> RenderTimings {
+        self.timings.get()
+    }
+
+    fn profile<R>(&self,
+                  stage: fn(&mut RenderTimings) -> &mut Duration,
+                  f: impl FnOnce() -> R) -> R {
+        if !self.profiling {
+            return f();
+        }
+        let start = Instant::now();
+        let res = f();
+        let mut timings = self.timings.get();
+        *stage(&mut timings) += start.elapsed();
+        self.timings.set(timings);
+        res
+    }
+
+    pub fn triangle_budget(&self) -> Option<usize> {
+        self.triangle_budget
+    }
+
+    // Caps the triangles drawn by each `render_model` call, counting a polygon
+    // face as the triangles of its fan. Faces are drawn whole, so the one
+    // reaching the budget may go over it. Every call starts where the previous
+    // one stopped, so the whole model shows up over a few frames; the number
+    // of triangles left out is reported in `RenderTimings::skipped`.
+    pub fn set_triangle_budget(&mut self, budget: Option<usize>) {
+        self.triangle_budget = budget;
+        self.budget_start.set(0);
+    }
+
+    pub fn line_depth_fade(&self) -> Option<(f64, f64)> {
+        self.line_depth_fade
+    }
+
+    // Dims depth tested lines (the wireframe overlay) with distance: full
+    // brightness at the `near` screen depth, fading to black at `far`.
+    pub fn set_line_depth_fade(&mut self, fade: Option<(f64, f64)>) {
+        self.line_depth_fade = fade;
+    }
+
+    pub fn material_texture(&self, material: usize) -> Option<&Texture> {
+        self.textures.get(&material)
+    }
+
+    // Diffuse texture of the faces using the material at index `material`
+    // of the model, e.g. decoded from its `map_Kd`. Only faces with texture
+    // coordinates on all corners are textured.
+    pub fn set_material_texture(&mut self, material: usize, texture: Option<Texture>) {
+        match texture {
+            Some(texture) => self.textures.insert(material, texture),
+            None => self.textures.remove(&material),
         };
+    }
+
+    pub fn default_texture(&self) -> Option<&Texture> {
+        self.default_texture.as_ref()
+    }
+
+    // Global fallback for textured faces whose material has no texture set,
+    // including faces without a material.
+    pub fn set_default_texture(&mut self, texture: Option<Texture>) {
+        self.default_texture = texture;
+    }
+
+    fn face_texture(&self, face: &Face) -> Option<&Texture> {
+        face.material
+            .and_then(|material| self.textures.get(&material))
+            .or(self.default_texture.as_ref())
+    }
 
-        let (p0, p1) = if p0.x > p1.x {
-            (p1, p0)
+    pub fn overdraw(&self) -> Option<Ref<'_, OverdrawBuffer>> {
+        self.overdraw.as_ref().map(|overdraw| overdraw.borrow())
+    }
+
+    // Counts the depth tests of every triangle drawn from now on, see
+    // `resolve_overdraw`.
+    pub fn set_overdraw(&mut self, enabled: bool) {
+        self.overdraw = enabled.then(|| RefCell::new(OverdrawBuffer::new(self.resolution)));
+    }
+
+    // Draws the overdraw counted since the last call as a heat map and starts
+    // counting afresh.
+    pub fn resolve_overdraw(&self, framebuffer: &mut FrameBuffer) -> Result<(), String> {
+        match &self.overdraw {
+            Some(overdraw) => {
+                overdraw.borrow().resolve(framebuffer)?;
+                overdraw.borrow_mut().clear();
+                Ok(())
+            }
+            None => Err("Overdraw counting is not enabled".to_string()),
+        }
+    }
+
+    pub fn face_caching(&self) -> bool {
+        self.face_cache.is_some()
+    }
+
+    // Keeps the projected triangles of the last model between `render_model`
+    // calls and reuses them while the model, view port and projection stay
+    // the same, which pays off for a static scene under a static camera. Hits
+    // are reported in `RenderTimings::cached`.
+    pub fn set_face_caching(&mut self, enabled: bool) {
+        self.face_cache = enabled.then(Default::default);
+    }
+
+    fn count_culled(&self) {
+        if self.profiling {
+            let mut timings = self.timings.get();
+            timings.culled += 1;
+            self.timings.set(timings);
+        }
+    }
+
+    fn count_flops(&self, flops: usize) {
+        if self.profiling {
+            let mut timings = self.timings.get();
+            timings.flops += flops;
+            self.timings.set(timings);
+        }
+    }
+
+    // Identity matrices are common for pre-transformed geometry, so skip
+    // multiplying by them.
+    fn combine(&self, lhs: Mat4x4f, rhs: Mat4x4f) -> Mat4x4f {
+        if rhs.is_identity() {
+            lhs
+        } else if lhs.is_identity() {
+            rhs
         } else {
-            (p0, p1)
+            self.count_flops(MAT4_MUL_FLOPS);
+            lhs * rhs
+        }
+    }
+
+    fn transform_vertices(&self, transform: Mat4x4f, vertices: [Vec3f; 3]) -> [Mat4x1f; 3] {
+        if transform.is_identity() {
+            return vertices.map(Mat4x1f::from);
+        }
+        self.count_flops(3 * MAT4_VEC_FLOPS);
+        vertices.map(|v| transform * Mat4x1f::from(v))
+    }
+
+    pub fn shadow_pcf(&self) -> usize {
+        self.shadow_pcf
+    }
+
+    // Side of the square kernel sampled around each shadow map lookup;
+    // 1 gives hard shadows. The kernel is centered on the looked up texel, so
+    // even sizes are rounded up to the next odd one: 2 samples 3x3.
+    pub fn set_shadow_pcf(&mut self, samples: usize) {
+        self.shadow_pcf = samples.max(1) | 1;
+    }
+
+    pub fn shadow_factor(&self, light: &Light, shadow_map: &ShadowMap, point: Vec3f) -> f64 {
+        if light.cast_shadows {
+            shadow_map.visibility(point, self.shadow_pcf, SHADOW_BIAS)
+        } else {
+            1.0
+        }
+    }
+
+    // Maps a screen position with its depth (see `ZBuffer::sample_depth`)
+    // back to the space the model was given in. `None` if the transform
+    // can't be inverted.
+    pub fn unproject(&self, screen: Vec3f, view_port: Mat4x4f, projection: Mat4x4f) -> Option<Vec3f> {
+        let inverse = (view_port * projection).invert()?;
+        Some(inverse * screen)
+    }
+
+    pub fn render_line<T: RenderTarget>(&self,
+                                        target: &mut T,
+                                        p0: Point,
+                                        p1: Point,
+                                        color: Rgb) -> Result<(), String> {
+        match clip_line(p0, p1, self.resolution.width, self.resolution.height) {
+            Some((p0, p1)) => trace_line(p0, p1, |x, y, _| target.draw_pixel(x as u32, y as u32, color)),
+            None => Ok(()),
+        }
+    }
+
+    // Xiaolin Wu's antialiased line between sub-pixel positions (z is
+    // ignored). Each step covers the two pixels straddling the ideal line,
+    // blended into the frame buffer by how close the line passes.
+    pub fn render_line_aa(&self, framebuffer: &mut FrameBuffer, p0: Vec3f, p1: Vec3f, color: Rgb) {
+        let steep = (p1.y - p0.y).abs() > (p1.x - p0.x).abs();
+        let (mut x0, mut y0, mut x1, mut y1) = if steep {
+            (p0.y, p0.x, p1.y, p1.x)
+        } else {
+            (p0.x, p0.y, p1.x, p1.y)
+        };
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let mut plot = |x: f64, y: f64, coverage: f64| {
+            let (x, y) = if steep { (y, x) } else { (x, y) };
+            if x >= 0.0 && y >= 0.0 {
+                framebuffer.blend_pixel(x as u32, y as u32, color, coverage);
+            }
         };
+        let fract = |v: f64| v - v.floor();
 
-        let dx = p1.x - p0.x;
-        let dy = p1.y - p0.y;
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
 
-        let iy = if p1.y > p0.y { 1 } else { -1 };
+        let mut endpoint = |x: f64, y: f64, gap: f64| {
+            let x_end = x.round();
+            let y_end = y + gradient * (x_end - x);
+            plot(x_end, y_end.floor(), (1.0 - fract(y_end)) * gap);
+            plot(x_end, y_end.floor() + 1.0, fract(y_end) * gap);
+            (x_end, y_end)
+        };
+        let (start, y_start) = endpoint(x0, y0, 1.0 - fract(x0 + 0.5));
+        let (end, _) = endpoint(x1, y1, fract(x1 + 0.5));
 
-        let derr2 = dy.abs() * 2;
-        let mut err2 = 0;
+        let mut y = y_start + gradient;
+        let mut x = start + 1.0;
+        while x < end {
+            plot(x, y.floor(), 1.0 - fract(y));
+            plot(x, y.floor() + 1.0, fract(y));
+            y += gradient;
+            x += 1.0;
+        }
+    }
+
+    pub fn render_line_depth_tested<T: RenderTarget>(&self,
+                                                     target: &mut T,
+                                                     zbuffer: &ZBuffer,
+                                                     p0: Vec3f,
+                                                     p1: Vec3f,
+                                                     color: Rgb) -> Result<(), String> {
+        let start = Point::new(p0.x as i32, p0.y as i32);
+        let end = Point::new(p1.x as i32, p1.y as i32);
+        trace_line(start, end, |x, y, t| {
+            if x < 0 || y < 0 ||
+                x as u32 >= self.resolution.width || y as u32 >= self.resolution.height {
+                return Ok(());
+            }
+            let z = p0.z + (p1.z - p0.z) * t;
+            let index = (x as u32 + self.resolution.width * y as u32) as usize;
+            if self.depth_func.passes(self.depth_func.offset(z, self.polygon_offset), zbuffer[index]) {
+                let color = match self.line_depth_fade {
+                    Some((near, far)) => {
+                        let brightness = 1.0 - ((z - near) / (far - near)).clamp(0.0, 1.0);
+                        Rgb::from(Vec3f::from(color) * brightness)
+                    }
+                    None => color,
+                };
+                target.draw_pixel(x as u32, y as u32, color)?;
+            }
+            Ok(())
+        })
+    }
+
+    // Reads back what has been drawn to the canvas so far. This stalls the GPU
+    // and is slow, so it is meant for screenshots rather than every frame; call
+    // it before `present`, after which the back buffer contents are undefined.
+    #[cfg(feature = "sdl")]
+    pub fn read_pixels<T>(&self, canvas: &sdl2::render::Canvas<T>) -> Result<FrameBuffer, String>
+        where T: sdl2::render::RenderTarget, {
+        let (width, height) = canvas.output_size()?;
+        let bytes = canvas.read_pixels(None, sdl2::pixels::PixelFormatEnum::RGB24)?;
+
+        let mut framebuffer = FrameBuffer::new((width, height));
+        for (index, rgb) in bytes.chunks_exact(3).enumerate() {
+            let index = index as u32;
+            framebuffer.draw_pixel(index % width, index / width, Rgb::new(rgb[0], rgb[1], rgb[2]))?;
+        }
+        Ok(framebuffer)
+    }
+
+    pub fn draw_text<T: RenderTarget>(&self,
+                                      target: &mut T,
+                                      x: u32,
+                                      y: u32,
+                                      text: &str,
+                                      color: Rgb) -> Result<(), String> {
+        for (index, c) in text.chars().enumerate() {
+            if let Some(rows) = glyph(c) {
+                let left = x + index as u32 * GLYPH_ADVANCE;
+                for (row, bits) in (0..GLYPH_HEIGHT).zip(rows) {
+                    for col in 0..GLYPH_WIDTH {
+                        if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                            target.draw_pixel(left + col, y + row, color)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // X, Y and Z axes as red, green and blue lines of length `size` from
+    // `screen_pos`, turned by the rotation part of the view only. Drawn back
+    // to front so the axis pointing at the viewer ends up on top.
+    pub fn render_axis_gizmo<T: RenderTarget>(&self,
+                                              target: &mut T,
+                                              view_rotation: Mat3x3f,
+                                              screen_pos: Point,
+                                              size: f64) -> Result<(), String> {
+        let mut axes = [
+            (Vec3f::new(1.0, 0.0, 0.0), Rgb::new(255, 0, 0)),
+            (Vec3f::new(0.0, 1.0, 0.0), Rgb::new(0, 255, 0)),
+            (Vec3f::new(0.0, 0.0, 1.0), Rgb::new(0, 0, 255)),
+        ].map(|(axis, color)| (view_rotation * axis, color));
+        axes.sort_by(|(a, _), (b, _)| a.z.total_cmp(&b.z));
+
+        for (axis, color) in axes {
+            let end = Point::new(screen_pos.x + (axis.x * size).round() as i32,
+                                 screen_pos.y + (axis.y * size).round() as i32);
+            self.render_line(target, screen_pos, end, color)?;
+        }
+        Ok(())
+    }
+
+    // Screen-space bounding rectangle (min and max corner) of the projected
+    // vertices. Vertices behind the camera (w <= 0) are left out; `None` if
+    // no vertex is in front of it.
+    pub fn screen_bounds(&self, model: &Model, view_port: Mat4x4f, projection: Mat4x4f) -> Option<(Point, Point)> {
+        let transform = view_port * projection;
+        model.vertices().iter()
+            .map(|&v| transform * Mat4x1f::from(v))
+            .filter(|clip| clip[3][0] > 0.0)
+            .map(Vec3f::from)
+            .fold(None, |bounds: Option<(Point, Point)>, p| {
+                let point = Point::new(p.x as i32, p.y as i32);
+                Some(match bounds {
+                    None => (point, point),
+                    Some((min, max)) => (
+                        Point::new(min.x.min(point.x), min.y.min(point.y)),
+                        Point::new(max.x.max(point.x), max.y.max(point.y)),
+                    ),
+                })
+            })
+    }
+
+    pub fn render_lines<T: RenderTarget>(&self,
+                                         target: &mut T,
+                                         model: &Model,
+                                         view_port: Mat4x4f,
+                                         projection: Mat4x4f,
+                                         color: Rgb) -> Result<(), String> {
+        let transform = view_port * projection;
+        let points = model.vertices().iter()
+            .map(|&v| {
+                let p = transform * v;
+                Point::new(p.x as i32, p.y as i32)
+            })
+            .collect::<Vec<Point>>();
+
+        for polyline in model.lines() {
+            for segment in polyline.windows(2) {
+                self.render_line(target, points[segment[0]], points[segment[1]], color)?;
+            }
+        }
+        Ok(())
+    }
 
-        let mut y = p0.y;
-        for x in p0.x..=p1.x {
-            if steep {
-                canvas.draw_point(Point::new(y, x))?;
-            } else {
-                canvas.draw_point(Point::new(x, y))?;
+    fn render_triangle_fn<T: RenderTarget>(&self,
+                                           target: &mut T,
+                                           zbuffer: &mut ZBuffer,
+                                           triangle: &Triangle,
+                                           color_fn: impl Fn([f64; 3]) -> Rgb) -> Result<(), String> {
+        let mut overdraw = self.overdraw.as_ref().map(|overdraw| overdraw.borrow_mut());
+        let pixels = triangle.rasterize_pixels(self.resolution.width, self.resolution.height);
+        for (x, y, bcs) in pixels {
+            let z = triangle
+                .vertices().iter()
+                .zip(bcs)
+                .map(|(v, g)| v.z * g)
+                .sum::<f64>();
+            let index = (x + self.resolution.width * y) as usize;
+            if let Some(overdraw) = overdraw.as_mut() {
+                overdraw.record(x, y);
             }
-            err2 += derr2;
-            if err2 > dx {
-                y += iy;
-                err2 -= dx * 2;
+            if self.depth_func.passes(z, zbuffer[index]) {
+                zbuffer[index] = z;
+                target.draw_pixel(x, y, color_fn(bcs))?;
             }
         }
         Ok(())
     }
 
-    fn render_triangle_fn(&self,
-                          canvas: &mut WindowCanvas,
-                          zbuffer: &mut Vec<f64>,
-                          triangle: &Triangle,
-                          color_fn: impl Fn([f64; 3]) -> Color) -> Result<(), String> {
-        let mut min_x = self.resolution.width - 1;
-        let mut min_y = self.resolution.height - 1;
-        let mut max_x = 0u32;
-        let mut max_y = 0u32;
-        for vertex in triangle.vertices() {
-            min_x = 0u32.max(min_x.min(vertex.x as u32));
-            min_y = 0u32.max(min_y.min(vertex.y as u32));
-            max_x = (self.resolution.width - 1).min(max_x.max(vertex.x as u32));
-            max_y = (self.resolution.height - 1).min(max_y.max(vertex.y as u32));
-        }
-
-        for x in min_x..=max_x {
-            for y in min_y..=max_y {
-                if let Some(bcs)  = triangle.barycentric(Vec3f::new(x as f64, y as f64, 0f64)) {
-                    let z = triangle
-                        .vertices().iter()
-                        .zip(bcs)
-                        .map(|(v, g)| v.z * g)
-                        .sum::<f64>();
-                    let index = (x + self.resolution.width * y) as usize;
-                    if zbuffer[index] < z {
-                        zbuffer[index] = z;
-                        canvas.set_draw_color(color_fn(bcs));
-                        canvas.draw_fpoint((x as f32, y as f32))?;
+    // Depth-only rasterization at each sample position of `zbuffer`. A
+    // sample is covered when it lies inside the triangle's projection onto
+    // the xy-plane, with pixel centers at integer coordinates.
+    pub fn render_triangle_depth_multisample(&self, zbuffer: &mut MultisampleZBuffer, triangle: &Triangle) {
+        let vertices = triangle.vertices();
+        let [p1, p2, p3] = vertices.map(|p| Vec3f::new(p.x, p.y, 0.0));
+        let flat = Triangle::new(p1, p2, p3);
+        let offsets: Vec<(f64, f64)> = zbuffer.sample_offsets().collect();
+
+        let resolution = zbuffer.resolution();
+        let bounds = Aabb::from_points(&[p1, p2, p3]);
+        let min_x = (bounds.min.x - 0.5).ceil().max(0.0) as u32;
+        let min_y = (bounds.min.y - 0.5).ceil().max(0.0) as u32;
+        let max_x = ((bounds.max.x + 0.5).floor().max(-1.0) + 1.0).min(resolution.width as f64) as u32;
+        let max_y = ((bounds.max.y + 0.5).floor().max(-1.0) + 1.0).min(resolution.height as f64) as u32;
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let depths = zbuffer.pixel_mut(x, y).unwrap();
+                for (depth, &(dx, dy)) in depths.iter_mut().zip(&offsets) {
+                    let Some(bcs) = flat.barycentric(Vec3f::new(x as f64 + dx, y as f64 + dy, 0.0)) else {
+                        continue;
+                    };
+                    let z = vertices.iter().zip(bcs).map(|(v, g)| v.z * g).sum::<f64>();
+                    if self.depth_func.passes(z, *depth) {
+                        *depth = z;
                     }
                 }
             }
         }
+    }
+
+    // Reduces every pixel of `samples` to a single depth in `zbuffer`
+    // following `depth_resolve`. Only covered samples, those not holding
+    // the clear value, count; pixels without any keep `zbuffer`'s clear
+    // value.
+    pub fn resolve_depth(&self, samples: &MultisampleZBuffer, zbuffer: &mut ZBuffer) -> Result<(), String> {
+        let resolution = samples.resolution();
+        if resolution != zbuffer.resolution() {
+            return Err(format!("Cannot resolve {}x{} samples into a {}x{} depth buffer",
+                               resolution.width, resolution.height,
+                               zbuffer.resolution().width, zbuffer.resolution().height));
+        }
+
+        for y in 0..resolution.height {
+            for x in 0..resolution.width {
+                let covered = samples.pixel(x, y).unwrap().iter()
+                    .copied()
+                    .filter(|&depth| depth != samples.clear_value());
+                let depth = match self.depth_resolve {
+                    DepthResolve::Nearest => covered.reduce(|nearest, depth| {
+                        if self.depth_func.passes(depth, nearest) { depth } else { nearest }
+                    }),
+                    DepthResolve::Average => {
+                        let (sum, count) = covered.fold((0.0, 0), |(sum, count), depth| (sum + depth, count + 1));
+                        (count > 0).then(|| sum / count as f64)
+                    }
+                };
+                zbuffer[(x + resolution.width * y) as usize] = depth.unwrap_or(zbuffer.clear_value());
+            }
+        }
         Ok(())
     }
 
-    pub fn render_triangle(&self,
-                           canvas: &mut WindowCanvas,
-                           zbuffer: &mut Vec<f64>,
-                           triangle: &Triangle,
-                           colors: [Vec3f; 3]) -> Result<(), String> {
+    pub fn render_triangle<T: RenderTarget>(&self,
+                                            target: &mut T,
+                                            zbuffer: &mut ZBuffer,
+                                            triangle: &Triangle,
+                                            colors: [Vec3f; 3]) -> Result<(), String> {
+        let varyings = colors.map(|color| Varyings { color, ..Default::default() });
+        self.render_triangle_shaded(target, zbuffer, triangle, &varyings, [1.0; 3], &vertex_color)
+    }
+
+    pub fn render_triangle_strip<T: RenderTarget>(&self,
+                                                  target: &mut T,
+                                                  zbuffer: &mut ZBuffer,
+                                                  vertices: &[Vec3f],
+                                                  colors: &[Vec3f]) -> Result<(), String> {
+        self.render_indexed(target, zbuffer, vertices, colors, triangle_strip(vertices.len()))
+    }
+
+    pub fn render_triangle_fan<T: RenderTarget>(&self,
+                                                target: &mut T,
+                                                zbuffer: &mut ZBuffer,
+                                                vertices: &[Vec3f],
+                                                colors: &[Vec3f]) -> Result<(), String> {
+        self.render_indexed(target, zbuffer, vertices, colors, triangle_fan(vertices.len()))
+    }
+
+    fn render_indexed<T: RenderTarget>(&self,
+                                       target: &mut T,
+                                       zbuffer: &mut ZBuffer,
+                                       vertices: &[Vec3f],
+                                       colors: &[Vec3f],
+                                       triangles: impl Iterator<Item = [usize; 3]>) -> Result<(), String> {
+        if colors.len() != vertices.len() {
+            return Err(format!("Expected {} vertex colors, got {}", vertices.len(), colors.len()));
+        }
+        for indices in triangles {
+            let [a, b, c] = indices.map(|i| vertices[i]);
+            self.render_triangle(target, zbuffer, &Triangle::new(a, b, c), indices.map(|i| colors[i]))?;
+        }
+        Ok(())
+    }
+
+    // Modulates the per-vertex base colors (0..255) by the diffuse light
+    // intensities and interpolates the result across the triangle.
+    pub fn render_triangle_lit<T: RenderTarget>(&self,
+                                                target: &mut T,
+                                                zbuffer: &mut ZBuffer,
+                                                triangle: &Triangle,
+                                                colors: [Vec3f; 3],
+                                                intensities: [f64; 3],
+                                                inv_w: [f64; 3]) -> Result<(), String> {
+        let varyings = [0, 1, 2].map(|i| Varyings {
+            color: colors[i] * intensities[i],
+            ..Default::default()
+        });
+        self.render_triangle_shaded(target, zbuffer, triangle, &varyings, inv_w, &vertex_color)
+    }
+
+    // Like `render_triangle_lit`, with the color of each pixel looked up in
+    // `texture` at the interpolated `uvs`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_textured_triangle<T: RenderTarget>(&self,
+                                                     target: &mut T,
+                                                     zbuffer: &mut ZBuffer,
+                                                     triangle: &Triangle,
+                                                     texture: &Texture,
+                                                     uvs: [VecUV2f; 3],
+                                                     intensities: [f64; 3],
+                                                     inv_w: [f64; 3]) -> Result<(), String> {
+        let varyings = [0, 1, 2].map(|i| Varyings {
+            color: Vec3f::new(intensities[i], intensities[i], intensities[i]),
+            uv: uvs[i],
+            ..Default::default()
+        });
+        let shader = |varyings: &Varyings| {
+            let texel = Vec3f::from(texture.sample(varyings.uv));
+            Rgb::from(Vec3f::new(texel.x * varyings.color.x,
+                                 texel.y * varyings.color.y,
+                                 texel.z * varyings.color.z))
+        };
+        self.render_triangle_shaded(target, zbuffer, triangle, &varyings, inv_w, &shader)
+    }
+
+    pub fn render_triangle_shaded<T: RenderTarget>(&self,
+                                                   target: &mut T,
+                                                   zbuffer: &mut ZBuffer,
+                                                   triangle: &Triangle,
+                                                   varyings: &[Varyings; 3],
+                                                   inv_w: [f64; 3],
+                                                   shader: &impl Shader) -> Result<(), String> {
         let color_fn = |bcs: [f64; 3]| {
-            let color = colors.into_iter()
-                .zip(bcs.into_iter())
-                .map(|(color, mul)| color * mul)
-                .reduce(|v1, v2| v1 + v2)
-                .unwrap();
-
-            Color::RGB(f64::clamp(color.x, 0.0, 255.0) as u8,
-                       f64::clamp(color.y, 0.0, 255.0) as u8,
-                       f64::clamp(color.z, 0.0, 255.0)  as u8)
+            shader.fragment(&Varyings::interpolate(varyings, bcs, inv_w))
         };
-        self.render_triangle_fn(canvas, zbuffer, triangle, color_fn)
+        self.render_triangle_fn(target, zbuffer, triangle, color_fn)
+    }
+
+    // Transparent pass for weighted blended OIT. Fragments are depth tested
+    // against the opaque pass but never write depth, so transparent surfaces
+    // don't hide each other. Weights are plain alpha: screen-space z isn't
+    // normalized, so depth based weights would depend on the scene scale.
+    pub fn render_triangle_transparent(&self,
+                                       oit: &mut OitBuffer,
+                                       zbuffer: &ZBuffer,
+                                       triangle: &Triangle,
+                                       color: Vec3f,
+                                       alpha: f64) -> Result<(), String> {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let pixels = triangle.rasterize_pixels(self.resolution.width, self.resolution.height);
+        for (x, y, bcs) in pixels {
+            let z = triangle
+                .vertices().iter()
+                .zip(bcs)
+                .map(|(v, g)| v.z * g)
+                .sum::<f64>();
+            let index = (x + self.resolution.width * y) as usize;
+            if self.depth_func.passes(z, zbuffer[index]) {
+                oit.accumulate(x, y, color, alpha, 1.0);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn render_model<T: RenderTarget>(&self,
+                                         target: &mut T,
+                                         zbuffer: &mut ZBuffer,
+                                         light_direction: &Vec3f,
+                                         model: &Model,
+                                         view_port: Mat4x4f,
+                                         projection: Mat4x4f) -> Result<(), String> {
+        // Instant isn't available everywhere (it panics on wasm32), so only
+        // read the clock when asked to
+        let start = self.profiling.then(Instant::now);
+        self.timings.set(Default::default());
+
+        let count = model.iter().count();
+        let fan_size = |face: &Face| face.len().saturating_sub(2);
+        let triangles: usize = model.iter().map(fan_size).sum();
+        let budget = self.triangle_budget.unwrap_or(triangles);
+        let first = if budget < triangles { self.budget_start.get() % count } else { 0 };
+
+        let mut cache = self.face_cache.as_ref().map(|cache| cache.borrow_mut());
+        if let Some(cache) = cache.as_mut() {
+            cache.validate(face_cache_key(model, view_port, projection), count);
+        }
+        let mut cached = 0;
+
+        let faces = model.iter().enumerate().skip(first)
+            .chain(model.iter().enumerate())
+            .take(count);
+        let mut drawn = 0;
+        let mut drawn_faces = 0;
+        for (face_id, face) in faces {
+            if drawn >= budget {
+                break;
+            }
+            drawn += fan_size(face);
+            drawn_faces += 1;
+            match cache.as_mut() {
+                Some(cache) if face.len() == 3 => {
+                    let projected = match cache.faces[face_id] {
+                        Some(projected) => {
+                            cached += 1;
+                            projected
+                        }
+                        None => *cache.faces[face_id]
+                            .insert(self.project_face(model, face, [0, 1, 2], view_port, projection)),
+                    };
+                    self.shade_face(target, zbuffer, light_direction, model, face, [0, 1, 2],
+                                    face_id, projected)?;
+                }
+                _ => self.render_face(target, zbuffer, light_direction, model, face, face_id,
+                                      view_port, projection)?,
+            }
+        }
+
+        self.budget_start.set((first + drawn_faces) % count.max(1));
+
+        let mut timings = self.timings.get();
+        timings.skipped = triangles.saturating_sub(drawn);
+        timings.cached = cached;
+        self.timings.set(timings);
+
+        if let Some(start) = start {
+            let mut timings = self.timings.get();
+            timings.faces = count;
+            timings.total = start.elapsed();
+            self.timings.set(timings);
+        }
+        Ok(())
     }
 
-    pub fn render_face(&self,
-                       canvas: &mut WindowCanvas,
-                       zbuffer: &mut Vec<f64>,
-                       light_direction: &Vec3f,
-                       face: &Face,
-                       view_port: Mat4x4f,
-                       projection: Mat4x4f) -> Result<(), String> {
-        if face.vertices.len() != 3 {
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_model_wireframe_overlay<T: RenderTarget>(&self,
+                                                           target: &mut T,
+                                                           zbuffer: &mut ZBuffer,
+                                                           light_direction: &Vec3f,
+                                                           model: &Model,
+                                                           view_port: Mat4x4f,
+                                                           projection: Mat4x4f,
+                                                           edge_color: Rgb) -> Result<(), String> {
+        self.render_model(target, zbuffer, light_direction, model, view_port, projection)?;
+
+        let transform = view_port * projection;
+        for face in model.iter() {
+            let points = face.vertices(model).map(|v| transform * v).collect::<Vec<Vec3f>>();
+            for (i, &p0) in points.iter().enumerate() {
+                let p1 = points[(i + 1) % points.len()];
+                self.render_line_depth_tested(target, zbuffer, p0, p1, edge_color)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_face<T: RenderTarget>(&self,
+                                        target: &mut T,
+                                        zbuffer: &mut ZBuffer,
+                                        light_direction: &Vec3f,
+                                        model: &Model,
+                                        face: &Face,
+                                        face_id: usize,
+                                        view_port: Mat4x4f,
+                                        projection: Mat4x4f) -> Result<(), String> {
+        if face.len() < 3 {
+            self.count_culled();
             return Ok(())
         }
 
-        let [p1, p2, p3] = [
-            face.vertices[0],
-            face.vertices[1],
-            face.vertices[2]
-        ].map(|v| (view_port * projection * Mat4x1f::from(v)).into());
+        // polygons are drawn as a fan around their first corner
+        for corners in triangle_fan(face.len()) {
+            let projected = self.project_face(model, face, corners, view_port, projection);
+            self.shade_face(target, zbuffer, light_direction, model, face, corners, face_id,
+                            projected)?;
+        }
+        Ok(())
+    }
 
-        let triangle = Triangle::new(p1, p2, p3);
+    // Projects the triangle of `face` made of the given corners. `None` if it
+    // collapses to a line on screen or doesn't project to finite points.
+    fn project_face(&self, model: &Model, face: &Face, corners: [usize; 3], view_port: Mat4x4f,
+                    projection: Mat4x4f) -> ProjectedFace {
+        let (clip, triangle) = self.profile(|t| &mut t.transform, || {
+            let transform = self.combine(view_port, projection);
+            let clip = self.transform_vertices(transform, corners.map(|i| face.vertex(model, i)));
+            let [p1, p2, p3] = clip.map(Vec3f::from);
+            (clip, Triangle::try_new(p1, p2, p3).filter(|triangle| !triangle.is_edge_on()))
+        });
+        triangle.map(|triangle| (triangle, clip.map(|c| 1.0 / c[3][0])))
+    }
 
-        if face.normals.len() != 3 {
-            let colors = [
-                Vec3f::new(255.0, 0.0, 0.0),
-                Vec3f::new(0.0, 255.0, 0.0),
-                Vec3f::new(0.0, 0.0, 255.0),
-            ];
+    #[allow(clippy::too_many_arguments)]
+    fn shade_face<T: RenderTarget>(&self,
+                                   target: &mut T,
+                                   zbuffer: &mut ZBuffer,
+                                   light_direction: &Vec3f,
+                                   model: &Model,
+                                   face: &Face,
+                                   corners: [usize; 3],
+                                   face_id: usize,
+                                   projected: ProjectedFace) -> Result<(), String> {
+        let Some((triangle, inv_w)) = projected else {
+            self.count_culled();
+            return Ok(())
+        };
+        if self.cull_mode.culls(&triangle, model.front_face()) {
+            self.count_culled();
+            return Ok(())
+        }
+        if self.shading_mode == ShadingMode::FaceId {
+            let color = Rgb::from(distinct_color(face_id));
+            return self.profile(|t| &mut t.rasterize, || {
+                self.render_triangle_fn(target, zbuffer, &triangle, |_| color)
+            });
+        }
 
-            self.render_triangle(canvas, zbuffer, &triangle, colors)
-        } else {
-            let intensities = face.normals.iter()
-                .map(|normal| light_direction.dot(normal))
-                .filter(|&intensity| intensity > 0.0)
-                .collect::<Vec<f64>>();
-
-            if intensities.len() == 3 {
-                let maybe_colors = [Vec3f::new(255.0, 255.0, 255.0); 3]
-                    .into_iter().zip(intensities.into_iter())
-                    .map(|(color, intensity)| color * intensity)
-                    .collect::<Vec<Vec3f>>();
-                let colors = <[Vec3f; 3]>::try_from(maybe_colors.as_slice()).unwrap();
-                self.render_triangle(canvas, zbuffer, &triangle, colors)
-            } else {
-                Ok(())
+        let has_normals = face.has_normals();
+        let normals = match corners.map(|i| face.normal(model, i)) {
+            [Some(n1), Some(n2), Some(n3)] => [n1, n2, n3],
+            _ => {
+                let [p1, p2, p3] = corners.map(|i| face.vertex(model, i));
+                [(p2 - p1).cross(&(p3 - p1)).normalize(); 3]
+            }
+        };
+
+        if self.shading_mode == ShadingMode::NormalRgb {
+            let varyings = normals.map(|normal| Varyings { normal, ..Default::default() });
+            return self.profile(|t| &mut t.rasterize, || {
+                self.render_triangle_shaded(target, zbuffer, &triangle, &varyings, inv_w,
+                                            &normal_color)
+            });
+        }
+
+        if !has_normals {
+            let colors = match self.missing_normal_policy {
+                MissingNormalPolicy::Rainbow => Some([
+                    Vec3f::new(255.0, 0.0, 0.0),
+                    Vec3f::new(0.0, 255.0, 0.0),
+                    Vec3f::new(0.0, 0.0, 255.0),
+                ]),
+                MissingNormalPolicy::SolidColor(color) => Some([color; 3]),
+                MissingNormalPolicy::FlatGeometric => None,
+            };
+            if let Some(colors) = colors {
+                let varyings = colors.map(|color| Varyings { color, ..Default::default() });
+                return self.profile(|t| &mut t.rasterize, || {
+                    self.render_triangle_shaded(target, zbuffer, &triangle, &varyings, inv_w,
+                                                &vertex_color)
+                });
             }
         }
+
+        let dots = self.profile(|t| &mut t.cull, || {
+            normals.map(|normal| light_direction.dot(&normal))
+        });
+
+        if self.diffuse_model.wraps() || dots.iter().all(|&dot| dot > 0.0) {
+            let intensities = dots.map(|dot| self.diffuse_model.intensity(dot));
+            if let Some(texture) = self.face_texture(face) {
+                if let [Some(t1), Some(t2), Some(t3)] = corners.map(|i| face.texture(model, i)) {
+                    return self.profile(|t| &mut t.rasterize, || {
+                        self.render_textured_triangle(target, zbuffer, &triangle, texture,
+                                                      [t1, t2, t3], intensities, inv_w)
+                    });
+                }
+            }
+            // vertex colors if the face has them, otherwise Kd of its
+            // material, white without one
+            let colors = match corners.map(|i| face.color(model, i)) {
+                [Some(c1), Some(c2), Some(c3)] => [c1, c2, c3].map(|color| color * 255.0),
+                _ => {
+                    let diffuse = face.material
+                        .map_or(Vec3f::new(1.0, 1.0, 1.0), |index| model.materials()[index].diffuse);
+                    [diffuse * 255.0; 3]
+                }
+            };
+            self.profile(|t| &mut t.rasterize, || {
+                self.render_triangle_lit(target, zbuffer, &triangle, colors, intensities, inv_w)
+            })
+        } else {
+            self.count_culled();
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,163 @@
+use crate::framebuffer::RenderTarget;
+use crate::geometry::{Mat4x4f, SqMatrix, Vec3f};
+use crate::model::Model;
+use crate::renderer::Renderer;
+use crate::shadow::Light;
+use crate::zbuffer::ZBuffer;
+
+// Applied as scale first, then rotation about x, y and z (radians), then
+// translation.
+#[derive(Copy, Clone, Debug)]
+pub struct Transform {
+    pub translation: Vec3f,
+    pub rotation: Vec3f,
+    pub scale: Vec3f,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            translation: Vec3f::new(0.0, 0.0, 0.0),
+            rotation: Vec3f::new(0.0, 0.0, 0.0),
+            scale: Vec3f::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Transform {
+    pub fn from_translation(translation: Vec3f) -> Self {
+        Transform { translation, ..Default::default() }
+    }
+
+    pub fn matrix(&self) -> Mat4x4f {
+        Mat4x4f::translation(self.translation) *
+            Mat4x4f::rotation_z(self.rotation.z) *
+            Mat4x4f::rotation_y(self.rotation.y) *
+            Mat4x4f::rotation_x(self.rotation.x) *
+            Mat4x4f::scale(self.scale)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Camera {
+    pub view_port: Mat4x4f,
+    pub projection: Mat4x4f,
+}
+
+// Camera orbiting the origin, starting out on the +z axis. `azimuth` and
+// `elevation` are as taken by `Vec3f::from_spherical`. Depths come out
+// OpenGL style, nearer is smaller, so render with `DepthFunc::Less`.
+#[derive(Copy, Clone, Debug)]
+pub struct PerspectiveCamera {
+    pub distance: f64,
+    pub azimuth: f64,
+    pub elevation: f64,
+    pub fov_y: f64,
+    pub near: f64,
+    pub far: f64,
+}
+
+impl PerspectiveCamera {
+    pub fn new(distance: f64) -> Self {
+        PerspectiveCamera {
+            distance,
+            azimuth: 0.0,
+            elevation: 0.0,
+            fov_y: std::f64::consts::FRAC_PI_4,
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+
+    pub fn eye(&self) -> Vec3f {
+        Vec3f::from_spherical(self.distance, self.azimuth, self.elevation)
+    }
+
+    pub fn view(&self) -> Mat4x4f {
+        Mat4x4f::look_at(self.eye(), Vec3f::new(0.0, 0.0, 0.0), Vec3f::new(0.0, 1.0, 0.0))
+    }
+
+    // Turns the camera around the origin, stopping just short of the poles
+    // where the up vector would be parallel to the view direction.
+    pub fn orbit(&mut self, azimuth: f64, elevation: f64) {
+        let limit = std::f64::consts::FRAC_PI_2 - 1e-3;
+        self.azimuth += azimuth;
+        self.elevation = (self.elevation + elevation).clamp(-limit, limit);
+    }
+
+    pub fn projection(&self, aspect: f64) -> Mat4x4f {
+        Mat4x4f::perspective(self.fov_y, aspect, self.near, self.far) * self.view()
+    }
+
+    // Light shining from the eye towards the origin, as a direction in the
+    // model space of an object placed with `world`, so whatever faces the
+    // camera stays lit as it orbits.
+    pub fn headlight(&self, world: Mat4x4f) -> Vec3f {
+        to_model_space(world, self.eye().normalize())
+    }
+
+    // Moves the camera back until a model within `radius` of the origin is
+    // entirely in front of the near plane.
+    pub fn clamp_distance(&mut self, radius: f64) {
+        self.distance = self.distance.max(radius + self.near);
+    }
+}
+
+// Takes a world space light direction into the model space of an object
+// placed with `world`: dotted with the model's own normals it gives the same
+// result as the world space light with the transformed normals. Keeps the
+// length, i.e. the strength, of the light.
+fn to_model_space(world: Mat4x4f, direction: Vec3f) -> Vec3f {
+    let Some(inverse) = world.upper_left().invert() else {
+        return direction
+    };
+    (inverse * direction).try_normalize().map_or(direction, |d| d * direction.norm())
+}
+
+#[derive(Default)]
+pub struct Node {
+    pub transform: Transform,
+    pub model: Option<Model>,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    pub fn new(transform: Transform, model: Option<Model>) -> Self {
+        Node { transform, model, children: Vec::new() }
+    }
+
+    pub fn add_child(&mut self, child: Node) -> &mut Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn world_transform(&self, parent_world: Mat4x4f) -> Mat4x4f {
+        parent_world * self.transform.matrix()
+    }
+
+    pub fn render<T: RenderTarget>(&self,
+                                   parent_world: Mat4x4f,
+                                   renderer: &Renderer,
+                                   target: &mut T,
+                                   zbuffer: &mut ZBuffer,
+                                   camera: &Camera,
+                                   lights: &[Light]) -> Result<(), String> {
+        let world = self.world_transform(parent_world);
+
+        if let Some(model) = &self.model {
+            // The renderer shades with a single direction. Lambert is linear
+            // in it, so summing the lights is exact wherever they all face
+            // the surface.
+            let direction = lights.iter()
+                .fold(Vec3f::new(0.0, 0.0, 0.0), |sum, light| sum + light.direction);
+            let light_direction = to_model_space(world, direction);
+            renderer.render_model(target, zbuffer, &light_direction, model,
+                                  camera.view_port, camera.projection * world)?;
+        }
+
+        for child in self.children.iter() {
+            child.render(world, renderer, target, zbuffer, camera, lights)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use crate::framebuffer::Rgb;
+use crate::geometry::{Vec3f, VecUV2f};
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Varyings {
+    pub color: Vec3f,
+    pub normal: Vec3f,
+    pub uv: VecUV2f,
+}
+
+impl Varyings {
+    // `inv_w` holds 1/w of each vertex in clip space; passing ones gives plain
+    // screen-space (affine) interpolation.
+    pub fn interpolate(varyings: &[Varyings; 3], bcs: [f64; 3], inv_w: [f64; 3]) -> Varyings {
+        let weights = [0, 1, 2].map(|i| bcs[i] * inv_w[i]);
+        let norm = 1.0 / weights.iter().sum::<f64>();
+
+        varyings.iter()
+            .zip(weights)
+            .fold(Varyings::default(), |res, (v, weight)| {
+                let weight = weight * norm;
+                Varyings {
+                    color: res.color + v.color * weight,
+                    normal: res.normal + v.normal * weight,
+                    uv: VecUV2f::new(res.uv.u + v.uv.u * weight, res.uv.v + v.uv.v * weight),
+                }
+            })
+    }
+}
+
+pub trait Shader {
+    fn fragment(&self, varyings: &Varyings) -> Rgb;
+}
+
+impl<F: Fn(&Varyings) -> Rgb> Shader for F {
+    fn fragment(&self, varyings: &Varyings) -> Rgb {
+        self(varyings)
+    }
+}
@@ -0,0 +1,72 @@
+use crate::common::Resolution;
+use crate::geometry::{Mat4x4f, Triangle, Vec3f};
+use crate::model::Model;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Light {
+    pub direction: Vec3f,
+    pub cast_shadows: bool,
+}
+
+impl Light {
+    pub fn new(direction: Vec3f) -> Light {
+        Light { direction, cast_shadows: true }
+    }
+}
+
+// Depth of the surfaces closest to the light, with larger z meaning closer
+// just like the renderer's default depth function.
+pub struct ShadowMap {
+    resolution: Resolution,
+    transform: Mat4x4f,
+    depth: Vec<f64>,
+}
+
+impl ShadowMap {
+    // `transform` maps world space into the shadow map's screen space, i.e.
+    // the viewport times the light's projection and view.
+    pub fn new<R: Into<Resolution>>(resolution: R, transform: Mat4x4f) -> Self {
+        let resolution = resolution.into();
+        let depth = vec![f64::MIN; (resolution.width * resolution.height) as usize];
+        ShadowMap { resolution, transform, depth }
+    }
+
+    // Polygon faces are split into triangle fans, like the main pass does.
+    pub fn render(&mut self, model: &Model) {
+        for triangle in model.triangles() {
+            let [p1, p2, p3] = triangle.vertices().map(|v| self.transform * v);
+            let triangle = Triangle::new(p1, p2, p3);
+
+            for (x, y, bcs) in triangle.rasterize_pixels(self.resolution.width, self.resolution.height) {
+                let z = p1.z * bcs[0] + p2.z * bcs[1] + p3.z * bcs[2];
+                let index = (x + self.resolution.width * y) as usize;
+                if self.depth[index] < z {
+                    self.depth[index] = z;
+                }
+            }
+        }
+    }
+
+    // Fraction of the `kernel` x `kernel` neighbourhood around the point's
+    // shadow map texel that sees the light: 1 is fully lit, 0 fully shadowed.
+    // An even `kernel` samples the next odd size, there being no center.
+    pub fn visibility(&self, point: Vec3f, kernel: usize, bias: f64) -> f64 {
+        let p = self.transform * point;
+        let radius = (kernel.max(1) / 2) as i64;
+        let (x, y) = (p.x.floor() as i64, p.y.floor() as i64);
+
+        let mut lit = 0;
+        let mut total = 0;
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let sx = (x + dx).clamp(0, self.resolution.width as i64 - 1) as u32;
+                let sy = (y + dy).clamp(0, self.resolution.height as i64 - 1) as u32;
+                if p.z + bias >= self.depth[(sx + self.resolution.width * sy) as usize] {
+                    lit += 1;
+                }
+                total += 1;
+            }
+        }
+        lit as f64 / total as f64
+    }
+}
@@ -0,0 +1,49 @@
+use crate::common::Resolution;
+use crate::framebuffer::Rgb;
+use crate::geometry::VecUV2f;
+
+// A decoded RGB image, stored row by row starting from the top.
+pub struct Texture {
+    resolution: Resolution,
+    pixels: Vec<Rgb>,
+}
+
+impl Texture {
+    pub fn new<R: Into<Resolution>>(resolution: R, pixels: Vec<Rgb>) -> Result<Texture, String> {
+        let resolution = resolution.into();
+        if resolution.width == 0 || resolution.height == 0 {
+            return Err("texture is empty".to_string());
+        }
+        let size = resolution.width as usize * resolution.height as usize;
+        if pixels.len() != size {
+            return Err(format!("texture of {}x{} needs {} pixels, got {}",
+                               resolution.width, resolution.height, size, pixels.len()));
+        }
+        Ok(Texture { resolution, pixels })
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Rgb> {
+        if x < self.resolution.width && y < self.resolution.height {
+            Some(self.pixels[self.index(x, y)])
+        } else {
+            None
+        }
+    }
+
+    // Nearest-neighbor lookup. v runs bottom to top as in OBJ files, and
+    // coordinates outside [0, 1] are clamped to the border.
+    pub fn sample(&self, uv: VecUV2f) -> Rgb {
+        let (width, height) = (self.resolution.width, self.resolution.height);
+        let x = (uv.u * width as f64).clamp(0.0, (width - 1) as f64) as u32;
+        let y = ((1.0 - uv.v) * height as f64).clamp(0.0, (height - 1) as f64) as u32;
+        self.pixels[self.index(x, y)]
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        x as usize + self.resolution.width as usize * y as usize
+    }
+}
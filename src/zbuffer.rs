@@ -0,0 +1,184 @@
+use std::io::Write;
+use std::ops::{Index, IndexMut};
+
+use crate::common::Resolution;
+
+pub struct ZBuffer {
+    resolution: Resolution,
+    clear_value: f64,
+    data: Vec<f64>,
+}
+
+impl ZBuffer {
+    // `clear_value` should be the sentinel of the renderer's depth function,
+    // see `Renderer::depth_clear_value`.
+    pub fn new<R: Into<Resolution>>(resolution: R, clear_value: f64) -> Self {
+        let resolution = resolution.into();
+        let data = vec![clear_value; (resolution.width * resolution.height) as usize];
+        ZBuffer { resolution, clear_value, data }
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    pub fn clear_value(&self) -> f64 {
+        self.clear_value
+    }
+
+    pub fn set_clear_value(&mut self, clear_value: f64) {
+        self.clear_value = clear_value;
+    }
+
+    pub fn clear(&mut self) {
+        self.data.fill(self.clear_value);
+    }
+
+    // Resizes and clears the buffer. The allocation is kept when the new size
+    // fits and only grows when needed, so window resizes don't churn memory.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.resolution = Resolution::new((width, height));
+        self.data.clear();
+        self.data.resize((width * height) as usize, self.clear_value);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+
+    // Binary 16-bit PGM with depths in [near, far] mapped linearly to
+    // [0, 65535]; depths outside the range, including the clear value, are
+    // clamped.
+    pub fn write_pgm16<W: Write>(&self, mut writer: W, near: f64, far: f64) -> std::io::Result<()> {
+        write!(writer, "P5\n{} {}\n65535\n", self.resolution.width, self.resolution.height)?;
+        let bytes: Vec<u8> = self.data.iter()
+            .flat_map(|&depth| {
+                let t = ((depth - near) / (far - near)).clamp(0.0, 1.0);
+                ((t * 65535.0).round() as u16).to_be_bytes()
+            })
+            .collect();
+        writer.write_all(&bytes)
+    }
+
+    pub fn depth(&self, x: u32, y: u32) -> Option<f64> {
+        if x < self.resolution.width && y < self.resolution.height {
+            Some(self.data[(x + self.resolution.width * y) as usize])
+        } else {
+            None
+        }
+    }
+
+    // Bilinear interpolation between the four samples around (x, y), with
+    // samples taken at integer pixel coordinates and clamped at the edges.
+    // Near uncovered pixels the nearest sample is returned instead, since
+    // blending with the clear value is meaningless.
+    pub fn sample_depth(&self, x: f64, y: f64) -> f64 {
+        let max_x = self.resolution.width.saturating_sub(1) as f64;
+        let max_y = self.resolution.height.saturating_sub(1) as f64;
+        let (x, y) = (x.clamp(0.0, max_x), y.clamp(0.0, max_y));
+
+        let (x0, y0) = (x.floor(), y.floor());
+        let (x1, y1) = ((x0 + 1.0).min(max_x), (y0 + 1.0).min(max_y));
+        let at = |x: f64, y: f64| self.data[(x as u32 + self.resolution.width * y as u32) as usize];
+
+        let samples = [at(x0, y0), at(x1, y0), at(x0, y1), at(x1, y1)];
+        if samples.contains(&self.clear_value) {
+            return at(x.round(), y.round());
+        }
+
+        let (tx, ty) = (x - x0, y - y0);
+        let top = samples[0] + (samples[1] - samples[0]) * tx;
+        let bottom = samples[2] + (samples[3] - samples[2]) * tx;
+        top + (bottom - top) * ty
+    }
+}
+
+impl Index<usize> for ZBuffer {
+    type Output = f64;
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl IndexMut<usize> for ZBuffer {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.data[index]
+    }
+}
+
+// Standard sample positions in 1/16 pixel units, relative to the pixel
+// center, for 1, 2, 4 and 8 samples per pixel.
+const SAMPLE_PATTERNS: [&[(i8, i8)]; 4] = [
+    &[(0, 0)],
+    &[(4, 4), (-4, -4)],
+    &[(-2, -6), (6, -2), (-6, 2), (2, 6)],
+    &[(1, -3), (-1, 3), (5, 1), (-3, -5), (-5, 5), (-7, -1), (3, 7), (7, -7)],
+];
+
+// Depth buffer holding several samples per pixel, reduced to a single depth
+// per pixel by `Renderer::resolve_depth`.
+pub struct MultisampleZBuffer {
+    resolution: Resolution,
+    samples: usize,
+    clear_value: f64,
+    data: Vec<f64>,
+}
+
+impl MultisampleZBuffer {
+    // `samples` must be 1, 2, 4 or 8.
+    pub fn new<R: Into<Resolution>>(resolution: R, samples: usize, clear_value: f64) -> Self {
+        assert!(samples.is_power_of_two() && samples <= 8, "Unsupported sample count {}", samples);
+        let resolution = resolution.into();
+        let data = vec![clear_value; (resolution.width * resolution.height) as usize * samples];
+        MultisampleZBuffer { resolution, samples, clear_value, data }
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    pub fn clear_value(&self) -> f64 {
+        self.clear_value
+    }
+
+    pub fn clear(&mut self) {
+        self.data.fill(self.clear_value);
+    }
+
+    // Offset of each sample from the pixel center, in pixels.
+    pub fn sample_offsets(&self) -> impl Iterator<Item = (f64, f64)> {
+        SAMPLE_PATTERNS[self.samples.trailing_zeros() as usize].iter()
+            .map(|&(x, y)| (x as f64 / 16.0, y as f64 / 16.0))
+    }
+
+    pub fn depth(&self, x: u32, y: u32, sample: usize) -> Option<f64> {
+        self.pixel(x, y).and_then(|depths| depths.get(sample).copied())
+    }
+
+    // All sample depths of a pixel.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<&[f64]> {
+        if x < self.resolution.width && y < self.resolution.height {
+            let start = (x + self.resolution.width * y) as usize * self.samples;
+            Some(&self.data[start..start + self.samples])
+        } else {
+            None
+        }
+    }
+
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> Option<&mut [f64]> {
+        if x < self.resolution.width && y < self.resolution.height {
+            let start = (x + self.resolution.width * y) as usize * self.samples;
+            Some(&mut self.data[start..start + self.samples])
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod test {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    use tinyrs::framebuffer::FrameBuffer;
+    use tinyrs::geometry::{Mat4x4f, Vec3f};
+    use tinyrs::model::Model;
+    use tinyrs::renderer::Renderer;
+    use tinyrs::zbuffer::ZBuffer;
+
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.with(|count| count.set(count.get() + 1));
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations() -> usize {
+        ALLOCATIONS.with(|count| count.get())
+    }
+
+    #[test]
+    fn test_render_face_does_not_allocate() {
+        const SIZE: u32 = 32;
+
+        let model = Model::uv_sphere(0.8, 12, 6);
+        let renderer = Renderer::new((SIZE, SIZE));
+        let mut framebuffer = FrameBuffer::new((SIZE, SIZE));
+        let mut zbuffer = ZBuffer::new((SIZE, SIZE), renderer.depth_clear_value());
+        let view_port = Mat4x4f::viewport(0.0, 0.0, SIZE as f64, SIZE as f64);
+        let light_direction = Vec3f::new(0.0, 0.0, 1.0);
+
+        let before = allocations();
+        for (face_id, face) in model.iter().enumerate() {
+            renderer.render_face(&mut framebuffer, &mut zbuffer, &light_direction, &model, face,
+                                 face_id, view_port, Mat4x4f::identity()).unwrap();
+        }
+        assert_eq!(allocations() - before, 0);
+        assert!(framebuffer.pixels().iter().any(|p| p.r > 0));
+    }
+}
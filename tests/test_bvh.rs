@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::bvh::Bvh;
+    use tinyrs::geometry::{Triangle, Vec3f};
+    use tinyrs::model::Model;
+
+    // Small deterministic generator, values in [-1, 1).
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> f64 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (self.0 >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+        }
+
+        fn vec3(&mut self) -> Vec3f {
+            Vec3f::new(self.next(), self.next(), self.next())
+        }
+    }
+
+    fn brute_force(triangles: &[Triangle], origin: Vec3f, dir: Vec3f) -> Option<(f64, usize)> {
+        triangles.iter().enumerate()
+            .filter_map(|(index, triangle)| triangle.intersect_ray(origin, dir).map(|t| (t, index)))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let model = Model::uv_sphere(1.0, 16, 8);
+        let triangles: Vec<Triangle> = model.triangles()
+            .chain(Model::cube(0.8).triangles())
+            .collect();
+        let bvh = Bvh::from_triangles(triangles.clone());
+        assert_eq!(bvh.len(), triangles.len());
+        assert_eq!(Bvh::new(&model).len(), model.triangles().count());
+
+        let mut rng = Lcg(7);
+        let mut hits = 0;
+        for _ in 0..500 {
+            let origin = rng.vec3() * 2.5;
+            // aim near the mesh so that most rays hit something
+            let dir = rng.vec3() * 1.2 - origin;
+            let expected = brute_force(&triangles, origin, dir);
+            let actual = bvh.intersect_ray(origin, dir);
+            match (expected, actual) {
+                (None, None) => {}
+                (Some((t, _)), Some((u, index))) => {
+                    hits += 1;
+                    assert!((t - u).abs() < 1e-9);
+                    // ties on shared edges may resolve to either triangle
+                    assert_eq!(triangles[index].intersect_ray(origin, dir), Some(u));
+                }
+                _ => panic!("bvh {:?} and brute force {:?} disagree", actual, expected),
+            }
+        }
+        assert!(hits > 100);
+    }
+
+    #[test]
+    fn test_empty() {
+        let bvh = Bvh::from_triangles(Vec::new());
+        assert!(bvh.is_empty());
+        assert!(bvh.intersect_ray(Vec3f::new(0.0, 0.0, 0.0), Vec3f::new(0.0, 0.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn test_nearest_hit() {
+        // two parallel quads, the ray starts between them
+        let quad = |y: f64| {
+            let [a, b, c, d] = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                .map(|(x, z)| Vec3f::new(x, y, z));
+            [Triangle::new(a, b, c), Triangle::new(a, c, d)]
+        };
+        let bvh = Bvh::from_triangles(quad(0.0).into_iter().chain(quad(3.0)).collect());
+
+        let origin = Vec3f::new(0.1, 1.0, 0.2);
+        let (t, _) = bvh.intersect_ray(origin, Vec3f::new(0.0, 1.0, 0.0)).unwrap();
+        assert!((t - 2.0).abs() < 1e-12);
+        let (t, _) = bvh.intersect_ray(origin, Vec3f::new(0.0, -2.0, 0.0)).unwrap();
+        assert!((t - 0.5).abs() < 1e-12);
+        assert!(bvh.intersect_ray(origin, Vec3f::new(1.0, 0.0, 0.0)).is_none());
+    }
+}
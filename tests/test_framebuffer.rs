@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::framebuffer::{FrameBuffer, RenderTarget, Rgb};
+    use tinyrs::geometry::{Triangle, Vec3f};
+    use tinyrs::renderer::Renderer;
+    use tinyrs::zbuffer::ZBuffer;
+
+    fn frame() -> FrameBuffer {
+        let mut framebuffer = FrameBuffer::new((8, 4));
+        framebuffer.fill(Rgb::new(10, 20, 30));
+        framebuffer
+    }
+
+    #[test]
+    fn test_identical_frames() {
+        frame().assert_eq(&frame(), 0);
+    }
+
+    #[test]
+    fn test_first_difference() {
+        let mut other = frame();
+        other.draw_pixel(5, 2, Rgb::new(10, 22, 30)).unwrap();
+        other.draw_pixel(6, 3, Rgb::new(0, 0, 0)).unwrap();
+
+        assert_eq!(frame().first_difference(&other, 0),
+                   Some((5, 2, Rgb::new(10, 20, 30), Rgb::new(10, 22, 30))));
+        assert_eq!(frame().first_difference(&other, 2).map(|(x, y, _, _)| (x, y)), Some((6, 3)));
+        assert_eq!(frame().first_difference(&other, 30), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Frame buffers differ at (5, 2)")]
+    fn test_assert_eq_reports_pixel() {
+        let mut other = frame();
+        other.draw_pixel(5, 2, Rgb::new(255, 255, 255)).unwrap();
+        frame().assert_eq(&other, 0);
+    }
+
+    #[test]
+    fn test_to_rgba8() {
+        let mut framebuffer = FrameBuffer::new((2, 2));
+        framebuffer.draw_pixel(0, 0, Rgb::new(255, 0, 0)).unwrap();
+        framebuffer.draw_pixel(1, 0, Rgb::new(0, 255, 0)).unwrap();
+        framebuffer.draw_pixel(0, 1, Rgb::new(0, 0, 255)).unwrap();
+        framebuffer.draw_pixel(1, 1, Rgb::new(1, 2, 3)).unwrap();
+
+        assert_eq!(framebuffer.to_rgba8(), vec![
+            255, 0, 0, 255,   0, 255, 0, 255,
+            0, 0, 255, 255,   1, 2, 3, 255,
+        ]);
+    }
+
+    #[test]
+    fn test_pixel_and_depth() {
+        let renderer = Renderer::new((16, 16));
+        let mut framebuffer = FrameBuffer::new((16, 16));
+        let mut zbuffer = ZBuffer::new((16, 16), renderer.depth_clear_value());
+
+        let triangle = Triangle::new(Vec3f::new(0.0, 0.0, 0.0),
+                                     Vec3f::new(15.0, 0.0, 0.3),
+                                     Vec3f::new(0.0, 15.0, 0.6));
+        let fill = Vec3f::new(200.0, 100.0, 50.0);
+        renderer.render_triangle(&mut framebuffer, &mut zbuffer, &triangle, [fill; 3]).unwrap();
+
+        // centroid of the triangle, where each vertex weighs a third
+        assert_eq!(framebuffer.pixel(5, 5), Some(Rgb::new(200, 100, 50)));
+        assert!((zbuffer.depth(5, 5).unwrap() - 0.3).abs() < 1e-9);
+
+        assert_eq!(framebuffer.pixel(15, 15), Some(Rgb::default()));
+        assert_eq!(framebuffer.pixel(16, 0), None);
+        assert_eq!(zbuffer.depth(0, 16), None);
+    }
+}
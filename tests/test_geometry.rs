@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test {
-    use tinyrs::geometry::{Mat3x3f, Mat4x1f, Mat4x4f, MatNxNf, SqMatrix, Triangle, Vec3f};
+    use tinyrs::geometry::{Aabb, Mat3x3f, Mat4x1f, Mat4x4f, MatNxNf, SqMatrix, Triangle, Vec2f, Vec3f, VecUV2f};
 
     const EPSILON: f64 = 1e-4_f64;
 
@@ -25,9 +25,159 @@ mod test {
         let v2 = Vec3f::new(0.0, 1.0, 0.0);
         let v3 = Vec3f::new(0.0, 0.0, 1.0);
 
-        assert!((v1.cross(&v2) - v3).norm() < EPSILON);
-        assert!((v2.cross(&v3) - v1).norm() < EPSILON);
-        assert!((v3.cross(&v1) - v2).norm() < EPSILON);
+        assert!(v1.cross(&v2).approx_eq(&v3, EPSILON));
+        assert!(v2.cross(&v3).approx_eq(&v1, EPSILON));
+        assert!(v3.cross(&v1).approx_eq(&v2, EPSILON));
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let v = Vec3f::new(1.0, 2.0, 3.0);
+        assert!(v.approx_eq(&Vec3f::new(1.00001, 1.99999, 3.0), EPSILON));
+        assert!(!v.approx_eq(&Vec3f::new(1.0, 2.0, 3.001), EPSILON));
+        assert!(!v.approx_eq(&Vec3f::new(f64::NAN, 2.0, 3.0), EPSILON));
+    }
+
+    #[test]
+    fn test_length_and_distance() {
+        let v = Vec3f::new(3.0, 4.0, 0.0);
+        assert!((v.length_squared() - 25.0).abs() < EPSILON);
+        assert!((v.norm() - 5.0).abs() < EPSILON);
+
+        let a = Vec3f::new(1.0, 2.0, 3.0);
+        let b = a + Vec3f::new(2.0, 3.0, 6.0);
+        assert!((a.distance(&b) - 7.0).abs() < EPSILON);
+        assert!((b.distance(&a) - 7.0).abs() < EPSILON);
+        assert!((a.distance_squared(&b) - 49.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_try_normalize() {
+        assert!(Vec3f::new(0.0, 0.0, 0.0).try_normalize().is_none());
+        assert!(Vec3f::new(f64::MIN_POSITIVE / 4.0, 0.0, 0.0).try_normalize().is_none());
+
+        let unit = Vec3f::new(0.0, 1.0, 0.0);
+        assert!((unit.try_normalize().unwrap() - unit).norm() < EPSILON);
+        let normalized = Vec3f::new(3.0, 0.0, 4.0).try_normalize().unwrap();
+        assert!((normalized - Vec3f::new(0.6, 0.0, 0.8)).norm() < EPSILON);
+    }
+
+    #[test]
+    fn test_vector_assign_ops() {
+        let v = Vec3f::new(1.0, -2.0, 3.0);
+        assert!((-v - Vec3f::new(-1.0, 2.0, -3.0)).norm() < EPSILON);
+
+        let mut sum = Vec3f::default();
+        sum += v;
+        sum += v;
+        assert!((sum - v * 2.0).norm() < EPSILON);
+        sum -= v;
+        assert!((sum - v).norm() < EPSILON);
+    }
+
+    #[test]
+    fn test_vector_division() {
+        let v = Vec3f::new(1.0, -2.0, 3.0);
+        assert!((v / 2.0 - Vec3f::new(0.5, -1.0, 1.5)).norm() < EPSILON);
+        assert!((v / Vec3f::new(2.0, 4.0, -3.0) - Vec3f::new(0.5, -0.5, -1.0)).norm() < EPSILON);
+
+        let infinite = v / 0.0;
+        assert!(infinite.x.is_infinite() && infinite.y.is_infinite() && infinite.z.is_infinite());
+
+        assert!(v.checked_div(0.0).is_none());
+        assert!(v.checked_div(f64::MIN_POSITIVE / 2.0).is_none());
+        assert!((v.checked_div(-1.0).unwrap() - Vec3f::new(-1.0, 2.0, -3.0)).norm() < EPSILON);
+    }
+
+    #[test]
+    fn test_lerp() {
+        let p1 = Vec3f::new(-0.5, -f64::sqrt(3.0) * 0.5, 0.0);
+        let p2 = Vec3f::new(1.0, 0.0, 0.0);
+        let mid12 = Vec3f::new((p1.x + p2.x) * 0.5, (p1.y + p2.y) * 0.5, 0.0);
+
+        assert!(p1.lerp(&p2, 0.0).approx_eq(&p1, EPSILON));
+        assert!(p1.lerp(&p2, 1.0).approx_eq(&p2, EPSILON));
+        assert!(p1.lerp(&p2, 0.5).approx_eq(&mid12, EPSILON));
+        assert!(p1.lerp(&p2, 2.0).approx_eq(&(p2 * 2.0 - p1), EPSILON));
+    }
+
+    #[test]
+    fn test_angle_between() {
+        let x = Vec3f::new(1.0, 0.0, 0.0);
+        assert!((x.angle_between(&Vec3f::new(0.0, 2.0, 0.0)) - std::f64::consts::FRAC_PI_2).abs() < EPSILON);
+        assert!(x.angle_between(&x).abs() < EPSILON);
+        assert!((x.angle_between(&-x) - std::f64::consts::PI).abs() < EPSILON);
+
+        let v = Vec3f::new(0.1, 0.7, 0.3).normalize();
+        assert!(!v.angle_between(&(v * 3.0)).is_nan());
+    }
+
+    #[test]
+    fn test_reflect() {
+        let reflected = Vec3f::new(1.0, -1.0, 0.0).reflect(&Vec3f::new(0.0, 1.0, 0.0));
+        assert!(reflected.approx_eq(&Vec3f::new(1.0, 1.0, 0.0), EPSILON));
+    }
+
+    #[test]
+    fn test_spherical() {
+        use std::f64::consts::{FRAC_PI_2, PI};
+
+        assert!(Vec3f::from_spherical(2.0, 0.0, 0.0).approx_eq(&Vec3f::new(0.0, 0.0, 2.0), EPSILON));
+        assert!(Vec3f::from_spherical(1.0, FRAC_PI_2, 0.0).approx_eq(&Vec3f::new(1.0, 0.0, 0.0), EPSILON));
+        assert!(Vec3f::from_spherical(1.0, PI, FRAC_PI_2).approx_eq(&Vec3f::new(0.0, 1.0, 0.0), EPSILON));
+
+        let vectors = [
+            Vec3f::new(1.0, 2.0, 3.0),
+            Vec3f::new(-0.5, -0.25, 0.1),
+            Vec3f::new(-2.0, 0.0, -1.0),
+            Vec3f::new(0.0, 3.0, 0.0),
+            Vec3f::new(0.0, -0.5, 0.0),
+            Vec3f::new(0.0, 0.0, 0.0),
+        ];
+        for v in vectors {
+            let (radius, azimuth, elevation) = v.to_spherical();
+            assert!(azimuth > -PI - EPSILON && azimuth <= PI + EPSILON);
+            assert!(elevation.abs() <= FRAC_PI_2 + EPSILON);
+            assert!(Vec3f::from_spherical(radius, azimuth, elevation).approx_eq(&v, EPSILON));
+        }
+
+        let (_, azimuth, elevation) = Vec3f::new(0.0, 3.0, 0.0).to_spherical();
+        assert_eq!(azimuth, 0.0);
+        assert!((elevation - FRAC_PI_2).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_vec2f() {
+        let a = Vec2f::new(3.0, 4.0);
+        let b = Vec2f::new(-1.0, 2.0);
+
+        assert!((a.dot(&b) - 5.0).abs() < EPSILON);
+        assert!((a.norm() - 5.0).abs() < EPSILON);
+        assert!((a.length_squared() - 25.0).abs() < EPSILON);
+        assert!(a.normalize().approx_eq(&Vec2f::new(0.6, 0.8), EPSILON));
+        assert!(Vec2f::default().try_normalize().is_none());
+        assert!((a.distance(&b) - 20.0_f64.sqrt()).abs() < EPSILON);
+        assert!((a.distance_squared(&b) - 20.0).abs() < EPSILON);
+
+        assert!((a + b).approx_eq(&Vec2f::new(2.0, 6.0), EPSILON));
+        assert!((a - b).approx_eq(&Vec2f::new(4.0, 2.0), EPSILON));
+        assert!((-a * 2.0).approx_eq(&Vec2f::new(-6.0, -8.0), EPSILON));
+        assert!((a / 2.0).approx_eq(&Vec2f::new(1.5, 2.0), EPSILON));
+        assert!(a.lerp(&b, 0.5).approx_eq(&Vec2f::new(1.0, 3.0), EPSILON));
+
+        let mut sum = a;
+        sum += b;
+        sum -= a;
+        assert!(sum.approx_eq(&b, EPSILON));
+
+        // counter-clockwise turn is positive
+        assert!(Vec2f::new(1.0, 0.0).cross(&Vec2f::new(0.0, 1.0)) > 0.0);
+        assert!(Vec2f::new(0.0, 1.0).cross(&Vec2f::new(1.0, 0.0)) < 0.0);
+
+        let uv = VecUV2f::from(Vec2f::new(0.25, 0.75));
+        assert!((uv.u - 0.25).abs() < EPSILON && (uv.v - 0.75).abs() < EPSILON);
+        assert!(Vec2f::from(uv).approx_eq(&Vec2f::new(0.25, 0.75), EPSILON));
+        assert!(Vec2f::from(Vec3f::new(1.0, 2.0, 3.0)).approx_eq(&Vec2f::new(1.0, 2.0), EPSILON));
     }
 
     #[test]
@@ -116,6 +266,224 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_triangle_try_new() {
+        let p1 = Vec3f::new(0.0, 0.0, 0.0);
+        let p2 = Vec3f::new(1.0, 0.0, 0.0);
+        let p3 = Vec3f::new(0.0, 1.0, 0.0);
+        assert!(Triangle::try_new(p1, p2, p3).is_some());
+
+        assert!(Triangle::try_new(p1, p2, Vec3f::new(2.0, 0.0, 0.0)).is_none());
+        assert!(Triangle::try_new(p1, p2, Vec3f::new(f64::NAN, 1.0, 0.0)).is_none());
+        assert!(Triangle::try_new(p1, p2, Vec3f::new(0.0, f64::INFINITY, 0.0)).is_none());
+
+        // collinear up to rounding
+        assert!(Triangle::try_new(p1, p2, Vec3f::new(2.0, 1e-14, 0.0)).is_none());
+        // tiny but well shaped is fine
+        assert!(Triangle::try_new(p1, p2 * 1e-6, p3 * 1e-6).is_some());
+
+        // a proper 3D triangle, though it has no area in xy
+        let upright = Triangle::try_new(p1, p2, Vec3f::new(0.5, 0.0, 1.0)).unwrap();
+        assert!(upright.is_edge_on());
+        assert!(!Triangle::try_new(p1, p2, p3).unwrap().is_edge_on());
+    }
+
+    #[test]
+    fn test_affine_constructors() {
+        let rotate = |m: Mat4x4f, v: Vec3f| Vec3f::from(m * Mat4x1f::from(v));
+        let x = Vec3f::new(1.0, 0.0, 0.0);
+        let y = Vec3f::new(0.0, 1.0, 0.0);
+        let z = Vec3f::new(0.0, 0.0, 1.0);
+        let quarter = std::f64::consts::FRAC_PI_2;
+
+        assert!(rotate(Mat4x4f::rotation_z(quarter), x).approx_eq(&y, EPSILON));
+        assert!(rotate(Mat4x4f::rotation_x(quarter), y).approx_eq(&z, EPSILON));
+        assert!(rotate(Mat4x4f::rotation_y(quarter), z).approx_eq(&x, EPSILON));
+
+        let p = Vec3f::new(1.0, 2.0, 3.0);
+        assert!(rotate(Mat4x4f::translation(Vec3f::new(1.0, -1.0, 0.5)), p)
+            .approx_eq(&Vec3f::new(2.0, 1.0, 3.5), EPSILON));
+        assert!(rotate(Mat4x4f::scale(Vec3f::new(2.0, 0.5, -1.0)), p)
+            .approx_eq(&Vec3f::new(2.0, 1.0, -3.0), EPSILON));
+
+        let composed = Mat4x4f::translation(y) * Mat4x4f::rotation_z(quarter);
+        assert!(rotate(composed, x).approx_eq(&(y * 2.0), EPSILON));
+    }
+
+    #[test]
+    fn test_transform_points() {
+        let transform = Mat4x4f::perspective(1.0, 1.5, 0.5, 10.0) * Mat4x4f::from([
+            0.0, -1.0, 0.0, 0.5,
+            1.0,  0.0, 0.0, -1.0,
+            0.0,  0.0, 2.0, -4.0,
+            0.0,  0.0, 0.0, 1.0,
+        ]);
+        let points: Vec<Vec3f> = (0..10)
+            .map(|i| Vec3f::new(i as f64 * 0.3 - 1.0, (i * i) as f64 * 0.05, (i % 3) as f64 * 0.5))
+            .collect();
+
+        let batch = transform.transform_points(&points);
+        let mut in_place = points.clone();
+        transform.transform_points_mut(&mut in_place);
+
+        for ((point, &batched), &transformed) in points.iter().zip(&batch).zip(&in_place) {
+            let expected = Vec3f::from(transform * Mat4x1f::from(*point));
+            assert!((batched - expected).norm() < EPSILON);
+            assert!((transformed - expected).norm() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_aabb() {
+        let empty = Aabb::empty();
+        assert!(empty.is_empty());
+        assert!(!empty.contains(Vec3f::new(0.0, 0.0, 0.0)));
+
+        let point = Vec3f::new(1.0, 2.0, 3.0);
+        let aabb = empty.extend(point);
+        assert!(!aabb.is_empty());
+        assert!(aabb.min.approx_eq(&point, EPSILON) && aabb.max.approx_eq(&point, EPSILON));
+
+        let aabb = Aabb::from_points(&[point, Vec3f::new(-1.0, 4.0, 0.0)]);
+        assert!(aabb.size().approx_eq(&Vec3f::new(2.0, 2.0, 3.0), EPSILON));
+    }
+
+    #[test]
+    fn test_ray_intersections() {
+        let aabb = Aabb::from_points(&[Vec3f::new(-1.0, -1.0, -1.0)])
+            .union(Aabb::from_points(&[Vec3f::new(1.0, 1.0, 1.0)]));
+        let dir = Vec3f::new(0.0, 0.0, 2.0);
+        assert_eq!(aabb.intersect_ray(Vec3f::new(0.5, 0.5, -5.0), dir), Some(2.0));
+        assert_eq!(aabb.intersect_ray(Vec3f::new(0.0, 0.0, 0.0), dir), Some(0.0));
+        assert_eq!(aabb.intersect_ray(Vec3f::new(0.0, 0.0, 5.0), dir), None);
+        assert_eq!(aabb.intersect_ray(Vec3f::new(2.0, 0.0, -5.0), dir), None);
+
+        let triangle = Triangle::new(Vec3f::new(0.0, 0.0, 1.0), Vec3f::new(1.0, 0.0, 1.0),
+                                     Vec3f::new(0.0, 1.0, 1.0));
+        let hit = triangle.intersect_ray(Vec3f::new(0.25, 0.25, 0.0), dir).unwrap();
+        assert!((hit - 0.5).abs() < EPSILON);
+        // from behind and from the far side
+        assert!(triangle.intersect_ray(Vec3f::new(0.25, 0.25, 2.0), -dir).is_some());
+        assert!(triangle.intersect_ray(Vec3f::new(0.25, 0.25, 2.0), dir).is_none());
+        assert!(triangle.intersect_ray(Vec3f::new(0.75, 0.75, 0.0), dir).is_none());
+        assert!(triangle.intersect_ray(Vec3f::new(0.0, 0.0, 0.0), Vec3f::new(1.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_transpose() {
+        let mat3 = Mat3x3f::from([
+            8.0, 4.0, 3.0,
+            5.0, 1.0, 0.0,
+            6.0, 7.0, 2.0,
+        ]);
+        let transposed = mat3.transpose();
+        assert_eq!(transposed[0][1], 5.0);
+        assert_eq!(transposed[2][1], 0.0);
+        let twice = transposed.transpose();
+        assert!((0..3).all(|row| (0..3).all(|col| twice[row][col] == mat3[row][col])));
+        let identity = Mat3x3f::identity().transpose();
+        assert!((0..3).all(|row| (0..3).all(|col| identity[row][col] == Mat3x3f::identity()[row][col])));
+
+        let mat4 = Mat4x4f::translation(Vec3f::new(1.0, 2.0, 3.0)) * Mat4x4f::rotation_y(0.3);
+        let transposed = mat4.transpose();
+        assert_eq!(transposed[3][2], 3.0);
+        let twice = transposed.transpose();
+        assert!((0..4).all(|row| (0..4).all(|col| twice[row][col] == mat4[row][col])));
+        let identity = Mat4x4f::identity().transpose();
+        assert!(identity.is_identity());
+
+        let data: Vec<f64> = (0..25).map(|i| i as f64).collect();
+        let mat5 = MatNxNf::new(5, data.clone());
+        let transposed = mat5.transpose();
+        assert_eq!(transposed[1][4], 21.0);
+        let twice = transposed.transpose();
+        assert!((0..5).all(|row| (0..5).all(|col| twice[row][col] == mat5[row][col])));
+        let identity = MatNxNf::identity(5).transpose();
+        assert!((0..5).all(|row| (0..5).all(|col| identity[row][col] == if row == col { 1.0 } else { 0.0 })));
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_scalar_and_reference_mul() {
+        let mat3 = Mat3x3f::from([
+            8.0, 4.0, 3.0,
+            5.0, 1.0, 0.0,
+            6.0, 7.0, 2.0,
+        ]);
+        let doubled = mat3 * 2.0;
+        assert!((0..3).all(|row| (0..3).all(|col| doubled[row][col] == 2.0 * mat3[row][col])));
+        let product = &mat3 * &doubled;
+        let owned = mat3 * doubled;
+        assert!((0..3).all(|row| (0..3).all(|col| product[row][col] == owned[row][col])));
+
+        let mat4 = Mat4x4f::translation(Vec3f::new(1.0, -2.0, 3.0)) * Mat4x4f::rotation_x(0.7);
+        let doubled = mat4 * 2.0;
+        assert!((0..4).all(|row| (0..4).all(|col| doubled[row][col] == 2.0 * mat4[row][col])));
+        let product = &(&mat4 * &doubled) * &mat4;
+        let owned = mat4 * doubled * mat4;
+        assert!((0..4).all(|row| (0..4).all(|col| product[row][col] == owned[row][col])));
+    }
+
+    #[test]
+    fn test_transform_direction() {
+        let direction = Vec3f::new(0.3, -0.4, 0.5);
+        let translation = Mat4x4f::translation(Vec3f::new(10.0, -20.0, 30.0));
+        assert!(translation.transform_direction(direction).approx_eq(&direction, EPSILON));
+        assert!(!(translation * direction).approx_eq(&direction, EPSILON));
+
+        let rotation = Mat4x4f::rotation_z(std::f64::consts::FRAC_PI_2);
+        let turned = (translation * rotation).transform_direction(Vec3f::new(1.0, 0.0, 0.0));
+        assert!(turned.approx_eq(&Vec3f::new(0.0, 1.0, 0.0), EPSILON));
+    }
+
+    #[test]
+    fn test_normal_matrix() {
+        let rotation = Mat4x4f::rotation_y(0.4) * Mat4x4f::rotation_x(-1.1);
+        let model = Mat4x4f::translation(Vec3f::new(3.0, -1.0, 2.0)) * rotation;
+        let upper_left = model.upper_left();
+        let normal_matrix = model.normal_matrix().unwrap();
+        for row in 0..3 {
+            for col in 0..3 {
+                assert_eq!(upper_left[row][col], rotation[row][col]);
+                assert!((normal_matrix[row][col] - upper_left[row][col]).abs() < EPSILON);
+            }
+        }
+
+        // under a non-uniform scale the normal stays perpendicular to the
+        // transformed surface
+        let scale = Mat4x4f::scale(Vec3f::new(4.0, 1.0, 0.5));
+        let tangent = Vec3f::new(1.0, -1.0, 0.0);
+        let normal = Vec3f::new(1.0, 1.0, 0.0);
+        let tangent = scale.upper_left() * tangent;
+        let normal = scale.normal_matrix().unwrap() * normal;
+        assert!(tangent.dot(&normal).abs() < EPSILON);
+
+        assert!(Mat4x4f::scale(Vec3f::new(1.0, 0.0, 1.0)).normal_matrix().is_none());
+    }
+
+    #[test]
+    fn test_trace() {
+        assert_eq!(Mat3x3f::identity().trace(), 3.0);
+        assert_eq!(Mat4x4f::identity().trace(), 4.0);
+        assert_eq!(MatNxNf::identity(6).trace(), 6.0);
+
+        // the product from test_mul_3x3f
+        let mat_p = Mat3x3f::from([
+            64.0, 98.0, 69.0,
+            19.0, 50.0, 30.0,
+            62.0, 93.0, 50.0,
+        ]);
+        assert_eq!(mat_p.trace(), 164.0);
+        assert_eq!(mat_p.transpose().trace(), 164.0);
+
+        let mat = MatNxNf::new(3, vec![
+            2.0, -1.0, 0.0,
+            -1.0, 2.0, -1.0,
+            0.0, -1.0, 2.0,
+        ]);
+        assert_eq!(mat.trace(), 6.0);
+    }
+
     #[test]
     fn test_mul_3x3f() {
         let mat_a = Mat3x3f::from([
@@ -293,6 +661,20 @@ mod test {
         assert!(diff[2]);
     }
 
+    #[test]
+    fn test_mul_vec3() {
+        let mat = Mat4x4f::from([
+            2.0, 3.0, 1.0, 5.0,
+            7.0, 4.0, 9.0, 8.0,
+            6.0, 5.0, 8.0, 7.0,
+            9.0, 2.0, 6.0, 5.0,
+        ]);
+
+        // same result as test_mul_vec_4x1 without the Mat4x1f round trip
+        let vec = mat * Vec3f::new(3.0, 5.0, 7.0);
+        assert!(vec.approx_eq(&Vec3f::new(33.0 / 84.0, 112.0 / 84.0, 106.0 / 84.0), EPSILON));
+    }
+
     #[test]
     fn test_invert_4x4f() {
         let mat = Mat4x4f::from([
@@ -378,6 +760,41 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_mul_4x4() {
+        let mat_a = MatNxNf::new(4, vec![
+            2.0, 3.0, 1.0, 5.0,
+            7.0, 4.0, 9.0, 8.0,
+            6.0, 5.0, 8.0, 7.0,
+            9.0, 2.0, 6.0, 5.0,
+        ]);
+        let mat_b = MatNxNf::new(4, vec![
+            1.0, 0.0, 2.0, -1.0,
+            3.0, 1.0, 0.0, 2.0,
+            -2.0, 4.0, 1.0, 0.0,
+            0.0, 1.0, -1.0, 3.0,
+        ]);
+        let expected = [
+            9.0, 12.0, 0.0, 19.0,
+            1.0, 48.0, 15.0, 25.0,
+            5.0, 44.0, 13.0, 25.0,
+            3.0, 31.0, 19.0, 10.0,
+        ];
+
+        let product = mat_a * mat_b;
+        for row in 0..4 {
+            for col in 0..4 {
+                assert!((product[row][col] - expected[4 * row + col]).abs() < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot multiply a 3x3 matrix by a 4x4 matrix")]
+    fn test_mul_dimension_mismatch() {
+        let _ = MatNxNf::identity(3) * MatNxNf::identity(4);
+    }
+
     #[test]
     fn test_singular_3x3() {
         let mat = MatNxNf::new(3, vec![
@@ -474,4 +891,323 @@ mod test {
 
         assert!(mat.det().abs() < EPSILON);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_to_vec3_affine() {
+        let translation = Mat4x4f::from([
+            1.0, 0.0, 0.0, 5.0,
+            0.0, 1.0, 0.0, 6.0,
+            0.0, 0.0, 1.0, 7.0,
+            0.0, 0.0, 0.0, 1.0,
+        ]);
+
+        let direction = Mat4x1f::from([1.0, 2.0, 3.0, 0.0]);
+        let res = (translation * direction).to_vec3_affine();
+        assert!((res - Vec3f::new(1.0, 2.0, 3.0)).norm() < EPSILON);
+
+        let point = Mat4x1f::from(Vec3f::new(1.0, 2.0, 3.0));
+        let res = (translation * point).to_vec3_affine();
+        assert!((res - Vec3f::new(6.0, 8.0, 10.0)).norm() < EPSILON);
+    }
+
+    #[test]
+    fn test_barycentric_outside() {
+        let triangle = Triangle::new(Vec3f::new(0.0, 0.0, 0.0),
+                                     Vec3f::new(3.0, 0.0, 0.0),
+                                     Vec3f::new(0.0, 3.0, 0.0));
+
+        assert!(triangle.barycentric(Vec3f::new(2.0, 2.0, 0.0)).is_none());
+        assert!(triangle.barycentric(Vec3f::new(-1.0, 1.0, 0.0)).is_none());
+        assert!(triangle.barycentric(Vec3f::new(1.0, 1.0, 0.0)).is_some());
+    }
+
+    #[test]
+    fn test_rasterize_pixels() {
+        let triangle = Triangle::new(Vec3f::new(0.0, 0.0, 0.5),
+                                     Vec3f::new(3.0, 0.0, -0.5),
+                                     Vec3f::new(0.0, 3.0, 0.25));
+
+        let mut pixels = triangle.rasterize_pixels(10, 10)
+            .map(|(x, y, _)| (x, y))
+            .collect::<Vec<(u32, u32)>>();
+        pixels.sort();
+
+        assert_eq!(pixels, vec![
+            (0, 0), (0, 1), (0, 2), (0, 3),
+            (1, 0), (1, 1), (1, 2),
+            (2, 0), (2, 1),
+            (3, 0),
+        ]);
+
+        for (x, y, [g1, g2, g3]) in triangle.rasterize_pixels(10, 10) {
+            assert!((g2 - x as f64 / 3.0).abs() < EPSILON);
+            assert!((g3 - y as f64 / 3.0).abs() < EPSILON);
+            assert!((g1 + g2 + g3 - 1.0).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_rasterize_pixels_clipped() {
+        let triangle = Triangle::new(Vec3f::new(-2.0, -2.0, 0.0),
+                                     Vec3f::new(4.0, -2.0, 0.0),
+                                     Vec3f::new(-2.0, 4.0, 0.0));
+
+        let pixels = triangle.rasterize_pixels(4, 4).collect::<Vec<_>>();
+        assert!(pixels.iter().all(|&(x, y, _)| x < 4 && y < 4));
+        assert_eq!(pixels.len(), 6);
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let mat = Mat3x3f::from([
+            4.0, 1.0, 2.0,
+            1.0, 3.0, 0.5,
+            2.0, 0.5, 5.0,
+        ]);
+
+        let (values, vectors) = mat.symmetric_eigen();
+        for (col, value) in [values.x, values.y, values.z].into_iter().enumerate() {
+            let v = Vec3f::new(vectors[0][col], vectors[1][col], vectors[2][col]);
+            let mv = mat * v;
+            assert!((v.norm() - 1.0).abs() < 1e-9);
+            assert!((mv - v * value).norm() < 1e-9);
+        }
+        assert!((values.x + values.y + values.z - 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_condition_estimate() {
+        let identity = MatNxNf::identity(4);
+        assert_eq!(identity.condition_estimate(), 1.0);
+
+        let well_conditioned = MatNxNf::new(3, vec![
+            4.0, 1.0, 0.0,
+            1.0, 3.0, 1.0,
+            0.0, 1.0, 2.0,
+        ]);
+        assert!(well_conditioned.condition_estimate() < 10.0);
+
+        let near_singular = MatNxNf::new(3, vec![
+            1.0, 2.0, 3.0,
+            4.0, 5.0, 6.0,
+            7.0, 8.0, 9.0 + 1e-9,
+        ]);
+        assert!(near_singular.condition_estimate() > 1e6);
+
+        let singular = MatNxNf::new(2, vec![
+            1.0, 2.0,
+            2.0, 4.0,
+        ]);
+        assert_eq!(singular.condition_estimate(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_non_finite_matrices() {
+        let mat = MatNxNf::new(3, vec![
+            1.0, 0.0, 0.0,
+            0.0, f64::NAN, 0.0,
+            0.0, 0.0, 1.0,
+        ]);
+        assert!(mat.invert().is_none());
+        assert!(mat.det().is_nan());
+        assert!(mat.solve(&[1.0, 2.0, 3.0]).is_none());
+
+        let mut mat4 = Mat4x4f::identity();
+        mat4[2][3] = f64::INFINITY;
+        assert!(mat4.invert().is_none());
+
+        let mut mat3 = Mat3x3f::identity();
+        mat3[0][0] = f64::NAN;
+        assert!(mat3.invert().is_none());
+    }
+
+    #[test]
+    fn test_solve() {
+        let mat = MatNxNf::new(3, vec![
+            2.0, 1.0, -1.0,
+            -3.0, -1.0, 2.0,
+            -2.0, 1.0, 2.0,
+        ]);
+        let x = mat.solve(&[8.0, -11.0, -3.0]).unwrap();
+        for (value, expected) in x.iter().zip([2.0, 3.0, -1.0]) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+
+        assert!(MatNxNf::new(2, vec![1.0, 2.0, 2.0, 4.0]).solve(&[1.0, 1.0]).is_none());
+
+        // the matrix from test_singular_3x3
+        let singular = MatNxNf::new(3, vec![
+            1.0, 2.0, 3.0,
+            4.0, 5.0, 6.0,
+            7.0, 8.0, 9.0,
+        ]);
+        assert!(singular.solve(&[1.0, 2.0, 3.0]).is_none());
+
+        // needs a row swap for the first pivot
+        let swapped = MatNxNf::new(3, vec![
+            0.0, 1.0, 2.0,
+            1.0, 0.0, 1.0,
+            2.0, 1.0, 0.0,
+        ]);
+        let x = swapped.solve(&[8.0, 4.0, 4.0]).unwrap();
+        for (value, expected) in x.iter().zip([1.0, 2.0, 3.0]) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Right-hand side should match matrix dimension")]
+    fn test_solve_dimension() {
+        MatNxNf::identity(3).solve(&[1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_from_rows_and_cols() {
+        let rows = vec![
+            vec![2.0, 1.0, -1.0],
+            vec![-3.0, -1.0, 2.0],
+            vec![-2.0, 1.0, 2.0],
+        ];
+        let mat = MatNxNf::from_rows(&rows);
+        let transposed = MatNxNf::from_cols(&rows);
+        for row in 0..3 {
+            for col in 0..3 {
+                assert_eq!(mat[row][col], rows[row][col]);
+                assert_eq!(transposed[col][row], rows[row][col]);
+            }
+        }
+
+        let basis = Mat3x3f::from_rows([
+            Vec3f::new(1.0, 2.0, 3.0),
+            Vec3f::new(4.0, 5.0, 6.0),
+            Vec3f::new(7.0, 8.0, 10.0),
+        ]);
+        assert_eq!(basis[1][2], 6.0);
+        assert_eq!(basis[2][2], 10.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Row 1 has 2 entries, expected 3 for a square matrix")]
+    fn test_from_rows_ragged() {
+        MatNxNf::from_rows(&[vec![1.0, 0.0, 0.0], vec![0.0, 1.0], vec![0.0, 0.0, 1.0]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Column 0 has 3 entries, expected 2 for a square matrix")]
+    fn test_from_cols_not_square() {
+        MatNxNf::from_cols(&[vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_lu() {
+        // the matrix from test_invert_4x4
+        let data = vec![
+            2.0, 3.0, 1.0, 5.0,
+            7.0, 4.0, 9.0, 8.0,
+            6.0, 5.0, 8.0, 7.0,
+            9.0, 2.0, 6.0, 5.0,
+        ];
+        let mat = MatNxNf::new(4, data.clone());
+        let (lower, upper, permutation) = mat.lu().unwrap();
+
+        let mut sorted = permutation.clone();
+        sorted.sort();
+        assert_eq!(sorted, vec![0, 1, 2, 3]);
+        for row in 0..4 {
+            assert_eq!(lower[row][row], 1.0);
+            for col in row + 1..4 {
+                assert_eq!(lower[row][col], 0.0);
+                assert_eq!(upper[col][row], 0.0);
+            }
+        }
+
+        let mut p = MatNxNf::new(4, vec![0.0; 16]);
+        for (row, &original) in permutation.iter().enumerate() {
+            p[row][original] = 1.0;
+        }
+        let pa = p * mat;
+        let lu = lower * upper;
+        for row in 0..4 {
+            for col in 0..4 {
+                assert!((pa[row][col] - lu[row][col]).abs() < EPSILON);
+            }
+        }
+
+        let singular = MatNxNf::new(3, vec![
+            1.0, 2.0, 3.0,
+            4.0, 5.0, 6.0,
+            7.0, 8.0, 9.0,
+        ]);
+        assert!(singular.lu().is_none());
+    }
+
+    #[test]
+    fn test_frustum() {
+        let (fov_y, aspect, near, far) = (1.2_f64, 4.0 / 3.0, 0.5, 20.0);
+        let top = near * (fov_y / 2.0).tan();
+        let right = top * aspect;
+
+        let frustum = Mat4x4f::frustum(-right, right, -top, top, near, far);
+        let perspective = Mat4x4f::perspective(fov_y, aspect, near, far);
+
+        let f = 1.0 / (fov_y / 2.0).tan();
+        let expected = Mat4x4f::from([
+            f / aspect, 0.0, 0.0,                               0.0,
+            0.0,        f,   0.0,                               0.0,
+            0.0,        0.0, (far + near) / (near - far),       2.0 * far * near / (near - far),
+            0.0,        0.0, -1.0,                              0.0,
+        ]);
+
+        for row in 0..4 {
+            for col in 0..4 {
+                assert!((frustum[row][col] - perspective[row][col]).abs() < f64::EPSILON);
+                assert!((expected[row][col] - perspective[row][col]).abs() < 1e-12);
+            }
+        }
+
+        // off-center frustum maps its corners onto the clip-space corners
+        let off_center = Mat4x4f::frustum(-1.0, 3.0, -0.5, 2.0, 1.0, 10.0);
+        let corner = Vec3f::from(off_center * Mat4x1f::from(Vec3f::new(3.0, 2.0, -1.0)));
+        assert!((corner.x - 1.0).abs() < 1e-12);
+        assert!((corner.y - 1.0).abs() < 1e-12);
+        assert!((corner.z + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_look_at() {
+        let eye = Vec3f::new(1.0, 2.0, 3.0);
+        let center = Vec3f::new(-2.0, 0.5, -1.0);
+        let view = Mat4x4f::look_at(eye, center, Vec3f::new(0.0, 1.0, 0.0));
+
+        let eye_view = Vec3f::from(view * Mat4x1f::from(eye));
+        assert!(eye_view.approx_eq(&Vec3f::new(0.0, 0.0, 0.0), 1e-12));
+
+        // the center lies straight ahead, down -z
+        let center_view = Vec3f::from(view * Mat4x1f::from(center));
+        let distance = (center - eye).norm();
+        assert!(center_view.approx_eq(&Vec3f::new(0.0, 0.0, -distance), 1e-12));
+
+        // a camera on +z looking at the origin is a plain translation
+        let view = Mat4x4f::look_at(Vec3f::new(0.0, 0.0, 5.0), Vec3f::new(0.0, 0.0, 0.0),
+                                    Vec3f::new(0.0, 1.0, 0.0));
+        let expected = Mat4x4f::translation(Vec3f::new(0.0, 0.0, -5.0));
+        for row in 0..4 {
+            for col in 0..4 {
+                assert!((view[row][col] - expected[row][col]).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_perspective_depth_range() {
+        let (near, far) = (0.5, 20.0);
+        let perspective = Mat4x4f::perspective(1.2, 16.0 / 9.0, near, far);
+        let ndc_z = |z: f64| Vec3f::from(perspective * Mat4x1f::from(Vec3f::new(0.3, -0.2, z))).z;
+
+        assert!((ndc_z(-near) + 1.0).abs() < 1e-12);
+        assert!((ndc_z(-far) - 1.0).abs() < 1e-12);
+        // depth increases monotonically with distance in between
+        assert!(ndc_z(-1.0) < ndc_z(-2.0));
+        assert!(ndc_z(-2.0) < ndc_z(-10.0));
+    }
+}
@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::framebuffer::{FrameBuffer, Rgb};
+    use tinyrs::geometry::Vec3f;
+    use tinyrs::headless::render_model;
+    use tinyrs::model::Model;
+    use tinyrs::renderer::{DepthFunc, Renderer};
+    use tinyrs::scene::PerspectiveCamera;
+
+    const SIZE: u32 = 32;
+
+    #[test]
+    fn test_render_model() {
+        let model = Model::uv_sphere(1.0, 16, 8);
+        let mut renderer = Renderer::new((SIZE, SIZE));
+        renderer.set_depth_func(DepthFunc::Less);
+        let projection = PerspectiveCamera::new(3.0).projection(1.0);
+
+        let mut framebuffer = FrameBuffer::new((SIZE, SIZE));
+        framebuffer.fill(Rgb::new(1, 2, 3));
+        render_model(&renderer, &mut framebuffer, &model, Vec3f::new(0.0, 0.0, 1.0), projection).unwrap();
+
+        // lit in the middle, cleared to black around it
+        let center = framebuffer.pixel(SIZE / 2, SIZE / 2).unwrap();
+        assert!(center.r > 0 && center.r == center.g && center.g == center.b);
+        assert_eq!(framebuffer.pixel(0, 0), Some(Rgb::default()));
+
+        let rgba = framebuffer.to_rgba8();
+        assert_eq!(rgba.len(), (SIZE * SIZE * 4) as usize);
+    }
+}
@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::geometry::Vec3f;
+    use tinyrs::model::Model;
+
+    #[test]
+    fn test_cube_hull() {
+        let interior = Vec3f::new(0.1, -0.2, 0.05);
+        let mut vertices = Model::cube(2.0).vertices().to_vec();
+        vertices.push(interior);
+        let model = Model::from_parts(vertices, Vec::new(), Vec::new(), Vec::new());
+
+        let hull = model.convex_hull();
+        assert_eq!(hull.stats().faces, 12);
+        assert_eq!(hull.vertices().len(), 8);
+        assert!(hull.vertices().iter().all(|v| v.x.abs() == 1.0 && v.y.abs() == 1.0 && v.z.abs() == 1.0));
+
+        // every face points away from the center
+        for triangle in hull.triangles() {
+            let [a, b, c] = triangle.vertices();
+            let normal = (b - a).cross(&(c - a));
+            assert!(normal.dot(&a) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_flat_hull() {
+        let vertices = vec![
+            Vec3f::new(0.0, 0.0, 0.0),
+            Vec3f::new(1.0, 0.0, 0.0),
+            Vec3f::new(1.0, 1.0, 0.0),
+            Vec3f::new(0.0, 1.0, 0.0),
+        ];
+        let model = Model::from_parts(vertices, Vec::new(), Vec::new(), Vec::new());
+        assert!(model.convex_hull().is_empty());
+    }
+}
@@ -0,0 +1,685 @@
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use tinyrs::errors::RenderError;
+    use tinyrs::geometry::{Aabb, Vec3f};
+    use tinyrs::model::Model;
+
+    const CUBE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/cube.obj");
+    const QUAD: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/quad.obj");
+    const COLORED_TRIANGLE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/colored_triangle.obj");
+
+    const TRIANGLE: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tinyrs_{}", name));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_peek_counts() {
+        let peeked = Model::peek_counts(CUBE).unwrap();
+        let model = Model::from_file(CUBE).unwrap();
+
+        assert_eq!(peeked, model.stats());
+        assert_eq!(peeked.vertices, 8);
+        assert_eq!(peeked.textures, 4);
+        assert_eq!(peeked.normals, 6);
+        assert_eq!(peeked.faces, 12);
+    }
+
+    #[test]
+    fn test_shared_vertices() {
+        let model = Model::from_file(CUBE).unwrap();
+
+        // every corner of the cube is shared by several faces but stored once
+        assert_eq!(model.vertices().len(), 8);
+        assert_eq!(model.normals().len(), 6);
+        let mut uses = [0; 8];
+        for face in model.iter() {
+            face.vertex_indices.iter().for_each(|&index| uses[index] += 1);
+        }
+        assert!(uses.iter().all(|&count| count >= 3));
+        assert_eq!(uses.iter().sum::<usize>(), 12 * 3);
+
+        // and faces resolve their corners through the shared table
+        for face in model.iter() {
+            for (corner, &index) in face.vertex_indices.iter().enumerate() {
+                assert!(face.vertex(&model, corner).approx_eq(&model.vertices()[index], 0.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_mtllib_relative_path() {
+        let dir = temp_dir("mtllib_relative");
+        fs::write(dir.join("model.mtl"), "newmtl skin\nmap_Kd skin.tga\n").unwrap();
+        fs::write(dir.join("model.obj"),
+                  format!("mtllib model.mtl\n{}f 1// 2// 3//\n", TRIANGLE)).unwrap();
+
+        let model = Model::from_file(dir.join("model.obj")).unwrap();
+
+        assert_eq!(model.materials().len(), 1);
+        assert_eq!(model.materials()[0].name, "skin");
+        assert_eq!(model.materials()[0].diffuse_map, Some(dir.join("skin.tga")));
+    }
+
+    #[test]
+    fn test_material_colors() {
+        let dir = temp_dir("material_colors");
+        fs::write(dir.join("model.mtl"),
+                  "newmtl red\nKa 0.1 0.1 0.1\nKd 0.8 0 0\nKs 0.5 0.5 0.5\nNs 32\n").unwrap();
+        fs::write(dir.join("model.obj"),
+                  format!("mtllib model.mtl\n{}f 1// 2// 3//\nusemtl red\nf 1// 2// 3//\n\
+                           usemtl unknown\nf 1// 2// 3//\n", TRIANGLE)).unwrap();
+
+        let model = Model::from_file(dir.join("model.obj")).unwrap();
+        let material = &model.materials()[0];
+        assert_eq!(material.name, "red");
+        assert_eq!((material.ambient.x, material.ambient.y, material.ambient.z), (0.1, 0.1, 0.1));
+        assert_eq!((material.diffuse.x, material.diffuse.y, material.diffuse.z), (0.8, 0.0, 0.0));
+        assert_eq!((material.specular.x, material.specular.y, material.specular.z), (0.5, 0.5, 0.5));
+        assert_eq!(material.shininess, 32.0);
+
+        let materials: Vec<Option<usize>> = model.iter().map(|face| face.material).collect();
+        assert_eq!(materials, vec![None, Some(0), None]);
+
+        fs::write(dir.join("model.mtl"), "newmtl red\nKd 0.8 zero 0\n").unwrap();
+        match Model::from_file(dir.join("model.obj")) {
+            Err(RenderError::MaterialParsingError(msg)) => assert!(msg.contains("line 2")),
+            _ => panic!("Malformed Kd should be reported"),
+        }
+    }
+
+    #[test]
+    fn test_comments_and_blank_lines() {
+        let dir = temp_dir("comments");
+        fs::write(dir.join("model.mtl"), "# exported\nnewmtl red # the only one\nKd 1 0 0 # red\n").unwrap();
+        fs::write(dir.join("model.obj"), "\
+            #comment without a space\n\
+            mtllib model.mtl # library\n\
+            \n   \n\
+            v 0 0 0  # inline comment\n\
+            \tv 1 0 0\n\
+            v 0 1 0 #\n\
+            vt 0 0 # u v\n\
+            vt 1 0\n\
+            vt 0 1\n\
+            vn 0 0 1 # up\n\
+            usemtl red # comment\n\
+            f 1/1/1 2/2/1 3/3/1 # face\n\
+            \tf 1/1/1 3/3/1 2/2/1\n").unwrap();
+
+        let model = Model::from_file(dir.join("model.obj")).unwrap();
+        let stats = model.stats();
+        assert_eq!((stats.vertices, stats.textures, stats.normals, stats.faces), (3, 3, 1, 2));
+        assert_eq!(Model::peek_counts(dir.join("model.obj")).unwrap(), stats);
+
+        let face = model.iter().next().unwrap();
+        assert_eq!(face.vertex_indices, vec![0, 1, 2]);
+        assert!(face.has_textures() && face.has_normals());
+        assert_eq!(face.material, Some(0));
+        assert_eq!(model.materials()[0].name, "red");
+        assert_eq!(model.materials()[0].diffuse.y, 0.0);
+    }
+
+    #[test]
+    fn test_mtllib_missing() {
+        let dir = temp_dir("mtllib_missing");
+        fs::write(dir.join("model.obj"),
+                  format!("mtllib missing.mtl\n{}f 1// 2// 3//\n", TRIANGLE)).unwrap();
+
+        match Model::from_file(dir.join("model.obj")) {
+            Err(RenderError::MaterialFileError(msg)) => assert!(msg.contains("missing.mtl")),
+            _ => panic!("Missing material library should be reported"),
+        }
+    }
+
+    #[test]
+    fn test_remove_degenerate_faces() {
+        let vertices = vec![
+            Vec3f::new(0.0, 0.0, 0.0),
+            Vec3f::new(1.0, 0.0, 0.0),
+            Vec3f::new(0.0, 1.0, 0.0),
+            Vec3f::new(2.0, 0.0, 0.0),
+        ];
+        let faces = vec![
+            vec![0, 1, 2],
+            vec![0, 0, 1],
+            vec![0, 1, 3],
+            vec![1, 2, 3],
+        ];
+        let mut model = Model::from_parts(vertices, Vec::new(), Vec::new(), faces);
+
+        assert_eq!(model.remove_degenerate_faces(), 2);
+        assert_eq!(model.iter().count(), 2);
+        assert_eq!(model.stats().faces, 2);
+        assert!(model.iter().all(|face| face.area(&model) > 0.0));
+    }
+
+    #[test]
+    fn test_polyline() {
+        let dir = temp_dir("polyline");
+        fs::write(dir.join("model.obj"), format!("{}l 1 3 2\n", TRIANGLE)).unwrap();
+
+        let model = Model::from_file(dir.join("model.obj")).unwrap();
+
+        assert_eq!(model.lines(), &[vec![0, 2, 1]]);
+        let resolved = model.lines()[0].iter()
+            .map(|&i| model.vertices()[i])
+            .collect::<Vec<Vec3f>>();
+        assert!((resolved[0] - Vec3f::new(0.0, 0.0, 0.0)).norm() < f64::EPSILON);
+        assert!((resolved[1] - Vec3f::new(0.0, 1.0, 0.0)).norm() < f64::EPSILON);
+        assert!((resolved[2] - Vec3f::new(1.0, 0.0, 0.0)).norm() < f64::EPSILON);
+
+        // relative indices count back from the last vertex, as in faces
+        fs::write(dir.join("relative.obj"), format!("{}l -3 -1 -2\n", TRIANGLE)).unwrap();
+        let model = Model::from_file(dir.join("relative.obj")).unwrap();
+        assert_eq!(model.lines(), &[vec![0, 2, 1]]);
+    }
+
+    #[test]
+    fn test_polyline_out_of_bounds() {
+        let dir = temp_dir("polyline_out_of_bounds");
+        fs::write(dir.join("model.obj"), format!("{}l 1 4\n", TRIANGLE)).unwrap();
+
+        assert!(matches!(Model::from_file(dir.join("model.obj")),
+                         Err(RenderError::LineParsingError(_))));
+    }
+
+    #[test]
+    fn test_zero_length_normal() {
+        let dir = temp_dir("zero_length_normal");
+        fs::write(dir.join("model.obj"), format!("{}vn 0 0 0\nf 1//1 2//1 3//1\n", TRIANGLE)).unwrap();
+
+        match Model::from_file(dir.join("model.obj")) {
+            Err(RenderError::NormalParsingError(msg)) => assert!(msg.contains("line 4")),
+            _ => panic!("Expected a normal parsing error"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "flate2")]
+    fn test_gzip() {
+        use std::io::Write;
+        use flate2::write::GzEncoder;
+
+        let dir = temp_dir("gzip");
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&fs::read(CUBE).unwrap()).unwrap();
+        fs::write(dir.join("cube.obj.gz"), encoder.finish().unwrap()).unwrap();
+
+        let plain = Model::from_file(CUBE).unwrap();
+        let compressed = Model::from_file(dir.join("cube.obj.gz")).unwrap();
+        assert_eq!(compressed.stats(), plain.stats());
+        assert_eq!(Model::peek_counts(dir.join("cube.obj.gz")).unwrap(), plain.stats());
+        for (a, b) in plain.iter().zip(compressed.iter()) {
+            assert_eq!(a.vertex_indices, b.vertex_indices);
+            for (va, vb) in a.vertices(&plain).zip(b.vertices(&compressed)) {
+                assert!((va - vb).norm() < f64::EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "flate2"))]
+    fn test_gzip_needs_feature() {
+        let dir = temp_dir("gzip_needs_feature");
+        fs::write(dir.join("model.obj"), [0x1f, 0x8b, 0x08, 0x00]).unwrap();
+
+        match Model::from_file(dir.join("model.obj")) {
+            Err(RenderError::FileReadError(err)) => assert!(err.to_string().contains("flate2")),
+            _ => panic!("Expected a file read error"),
+        }
+    }
+
+    #[test]
+    fn test_principal_axes() {
+        let direction = Vec3f::new(1.0, 2.0, -0.5).normalize();
+        let side = direction.cross(&Vec3f::new(0.0, 0.0, 1.0)).normalize();
+        let vertices: Vec<Vec3f> = (-10..=10)
+            .flat_map(|i| [-0.2, 0.2].map(|across| {
+                Vec3f::new(3.0, -1.0, 2.0) + direction * i as f64 + side * across
+            }))
+            .collect();
+
+        let model = Model::from_parts(vertices.clone(), Vec::new(), Vec::new(), Vec::new());
+        let rotation = model.principal_axes();
+
+        let dominant = rotation * direction;
+        assert!((dominant.x.abs() - 1.0).abs() < 1e-9);
+        assert!(dominant.y.abs() < 1e-9);
+        assert!(dominant.z.abs() < 1e-9);
+
+        let spread = |axis: fn(&Vec3f) -> f64| {
+            let values: Vec<f64> = vertices.iter().map(|&v| axis(&(rotation * v))).collect();
+            values.iter().cloned().fold(f64::MIN, f64::max) - values.iter().cloned().fold(f64::MAX, f64::min)
+        };
+        assert!(spread(|v| v.x) > 10.0 * spread(|v| v.y));
+        assert!(spread(|v| v.y) > spread(|v| v.z));
+    }
+
+    #[test]
+    fn test_negative_indices() {
+        let dir = temp_dir("negative_indices");
+        fs::write(dir.join("model.obj"),
+                  format!("v 5 5 5\n{}vt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 1\n\
+                           f -1/-3/-1 -2/-2/-1 -3/-1/-1\n", TRIANGLE)).unwrap();
+
+        let model = Model::from_file(dir.join("model.obj")).unwrap();
+        let face = model.iter().next().unwrap();
+        assert_eq!(face.vertex_indices, vec![3, 2, 1]);
+        assert_eq!(face.texture_indices, vec![0, 1, 2]);
+        assert_eq!(face.normal_indices, vec![0, 0, 0]);
+        let texture = face.texture(&model, 2).unwrap();
+        assert_eq!((texture.u, texture.v), (0.0, 1.0));
+        assert_eq!(face.normal(&model, 0).unwrap().z, 1.0);
+
+        fs::write(dir.join("out_of_range.obj"), format!("{}f -1// -2// -4//\n", TRIANGLE)).unwrap();
+        match Model::from_file(dir.join("out_of_range.obj")) {
+            Err(RenderError::FaceParsingError(msg)) => assert!(msg.contains("-4")),
+            _ => panic!("Out of range negative index should be reported"),
+        }
+    }
+
+    #[test]
+    fn test_groups() {
+        let dir = temp_dir("groups");
+        fs::write(dir.join("model.obj"),
+                  format!("{}v 1 1 0\nf 1// 2// 3//\n\
+                           g body\nf 1// 2// 3//\nf 2// 4// 3//\n\
+                           g empty\n\
+                           o eyes\nf 1// 2// 4//\n", TRIANGLE)).unwrap();
+
+        let model = Model::from_file(dir.join("model.obj")).unwrap();
+        let groups: Vec<(&str, usize)> = model.groups()
+            .map(|(name, faces)| (name, faces.len()))
+            .collect();
+        assert_eq!(groups, vec![("", 1), ("body", 2), ("eyes", 1)]);
+
+        let model = Model::from_file(CUBE).unwrap();
+        let groups: Vec<(&str, usize)> = model.groups()
+            .map(|(name, faces)| (name, faces.len()))
+            .collect();
+        assert_eq!(groups, vec![("", 12)]);
+    }
+
+    #[test]
+    fn test_vertex_colors() {
+        let model = Model::from_file(COLORED_TRIANGLE).unwrap();
+        let face = model.iter().next().unwrap();
+        let colors: Vec<(f64, f64, f64)> = (0..3)
+            .map(|i| face.color(&model, i).unwrap())
+            .map(|color| (color.x, color.y, color.z))
+            .collect();
+        assert_eq!(colors, vec![(1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (0.0, 0.0, 1.0)]);
+        let vertex = face.vertex(&model, 1);
+        assert_eq!((vertex.x, vertex.y, vertex.z), (1.0, -1.0, 0.0));
+
+        // a fourth value is the homogeneous w, not a color
+        let dir = temp_dir("vertex_colors");
+        fs::write(dir.join("model.obj"),
+                  "v 0 0 0 1\nv 1 0 0 1 0 0\nv 0 1 0 0 1 0\nf 1// 2// 3//\nf 2// 3// 2//\n").unwrap();
+        let model = Model::from_file(dir.join("model.obj")).unwrap();
+        let colored: Vec<bool> = (0..3).map(|i| model.iter().next().unwrap().color(&model, i).is_some())
+            .collect();
+        assert_eq!(colored, vec![false, true, true]);
+    }
+
+    #[test]
+    fn test_vertex_w() {
+        let dir = temp_dir("vertex_w");
+        fs::write(dir.join("model.obj"), "v 1 2 3\nv 2 4 6 2\nv 1 2 3 1.0\nf 1// 2// 3//\n").unwrap();
+        let model = Model::from_file(dir.join("model.obj")).unwrap();
+        let vertices: Vec<(f64, f64, f64)> = model.vertices().iter()
+            .map(|v| (v.x, v.y, v.z))
+            .collect();
+        assert_eq!(vertices, vec![(1.0, 2.0, 3.0); 3]);
+
+        fs::write(dir.join("zero.obj"), "v 1 2 3\nv 1 2 3 0\n").unwrap();
+        match Model::from_file(dir.join("zero.obj")) {
+            Err(RenderError::VertexParsingError(msg)) => assert!(msg.contains("line 2")),
+            _ => panic!("Zero w should be reported"),
+        }
+    }
+
+    #[test]
+    fn test_parse_error_context() {
+        let dir = temp_dir("parse_error_context");
+        let error = |name: &str, contents: String| {
+            fs::write(dir.join(name), contents).unwrap();
+            Model::from_file(dir.join(name)).err().unwrap().to_string()
+        };
+
+        let msg = error("vertex.obj", format!("{}v 1.0 2.0 abc # oops\n", TRIANGLE));
+        assert!(msg.starts_with("Unable to parse vertex: at line 4:"), "{}", msg);
+        assert!(msg.contains("coordinate z"), "{}", msg);
+        assert!(msg.contains("\"abc\" (argument 3)"), "{}", msg);
+        assert!(msg.contains("\"v 1.0 2.0 abc # oops\""), "{}", msg);
+
+        let msg = error("face.obj", format!("{}f 1// 2// x//\n", TRIANGLE));
+        assert!(msg.contains("invalid vertex index format \"x\""), "{}", msg);
+        assert!(msg.contains("\"f 1// 2// x//\""), "{}", msg);
+
+        let msg = error("color.obj", "v 0 0 0 1 0 zero\n".to_string());
+        assert!(msg.contains("coordinate b format \"zero\" (argument 6)"), "{}", msg);
+    }
+
+    #[test]
+    fn test_face_bounding_box() {
+        let vertices = vec![
+            Vec3f::new(-1.0, 2.0, 0.5),
+            Vec3f::new(3.0, -0.5, 0.0),
+            Vec3f::new(0.5, 1.0, -2.0),
+        ];
+        let model = Model::from_parts(vertices.clone(), Vec::new(), Vec::new(), vec![vec![0, 1, 2]]);
+
+        let aabb = model.iter().next().unwrap().bounding_box(&model);
+        assert!(aabb.min.approx_eq(&Vec3f::new(-1.0, -0.5, -2.0), f64::EPSILON));
+        assert!(aabb.max.approx_eq(&Vec3f::new(3.0, 2.0, 0.5), f64::EPSILON));
+        assert!(vertices.iter().all(|&v| aabb.contains(v)));
+        assert!(!aabb.contains(Vec3f::new(3.5, 0.0, 0.0)));
+        assert!(aabb.center().approx_eq(&Vec3f::new(1.0, 0.75, -0.75), f64::EPSILON));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let vertices = vec![
+            Vec3f::new(-1.0, 2.0, 0.5),
+            Vec3f::new(3.0, 4.0, -0.5),
+            Vec3f::new(1.0, -2.0, 1.5),
+        ];
+        let model = Model::from_parts(vertices, Vec::new(), Vec::new(), vec![vec![0, 1, 2]]);
+
+        let aabb = model.bounding_box();
+        assert!(aabb.min.approx_eq(&Vec3f::new(-1.0, -2.0, -0.5), 1e-12));
+        assert!(aabb.max.approx_eq(&Vec3f::new(3.0, 4.0, 1.5), 1e-12));
+        assert!(model.center().unwrap().approx_eq(&Vec3f::new(1.0, 1.0, 0.5), 1e-12));
+
+        let empty = Model::from_parts(Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        assert!(empty.center().is_none());
+    }
+
+    #[test]
+    fn test_normalize_to_unit_cube() {
+        let vertices = vec![
+            Vec3f::new(10.0, 20.0, 30.0),
+            Vec3f::new(14.0, 20.0, 30.0),
+            Vec3f::new(10.0, 22.0, 31.0),
+        ];
+        let mut model = Model::from_parts(vertices, Vec::new(), Vec::new(), vec![vec![0, 1, 2]]);
+        model.normalize_to_unit_cube();
+
+        let aabb = model.bounding_box();
+        assert!(aabb.center().approx_eq(&Vec3f::new(0.0, 0.0, 0.0), 1e-12));
+        assert!(aabb.size().approx_eq(&Vec3f::new(1.0, 0.5, 0.25), 1e-12));
+        assert!(aabb.min.approx_eq(&Vec3f::new(-0.5, -0.25, -0.125), 1e-12));
+        assert!(aabb.max.approx_eq(&Vec3f::new(0.5, 0.25, 0.125), 1e-12));
+
+        let mut empty = Model::from_parts(Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        empty.normalize_to_unit_cube();
+        assert!(empty.bounding_box().is_empty());
+    }
+
+    #[test]
+    fn test_normalize_millimeter_model() {
+        // a sphere in millimeters, far off the origin
+        let sphere = Model::uv_sphere(250.0, 12, 6);
+        let mut model = Model::from_parts(
+            sphere.vertices().iter().map(|&v| v + Vec3f::new(1000.0, -400.0, 80.0)).collect(),
+            Vec::new(), Vec::new(),
+            sphere.iter().map(|face| face.vertex_indices.clone()).collect());
+        model.normalize_to_unit_cube();
+
+        let unit = Aabb::empty()
+            .extend(Vec3f::new(-0.5, -0.5, -0.5))
+            .extend(Vec3f::new(0.5, 0.5, 0.5));
+        assert!(model.vertices().iter().all(|&v| unit.contains(v)));
+        assert!(model.iter().flat_map(|face| face.vertices(&model)).all(|v| unit.contains(v)));
+
+        let size = model.bounding_box().size();
+        assert!((size.x.max(size.y).max(size.z) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_quad_triangles() {
+        let model = Model::from_file(QUAD).unwrap();
+        assert_eq!(model.stats().faces, 1);
+        let face = model.iter().next().unwrap();
+        assert_eq!(face.len(), 4);
+        assert_eq!(face.normal_indices.len(), 4);
+
+        let triangles: Vec<_> = model.triangles().collect();
+        assert_eq!(triangles.len(), 2);
+        let area: f64 = triangles.iter()
+            .map(|t| {
+                let [a, b, c] = t.vertices();
+                (b - a).cross(&(c - a)).norm() / 2.0
+            })
+            .sum();
+        assert!((area - 1.6 * 1.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_triangles() {
+        let vertices = vec![
+            Vec3f::new(0.0, 0.0, 0.0),
+            Vec3f::new(1.0, 0.0, 0.0),
+            Vec3f::new(1.0, 1.0, 0.0),
+            Vec3f::new(0.0, 1.0, 0.0),
+            Vec3f::new(2.0, 0.0, 0.0),
+            Vec3f::new(2.0, 1.0, 0.0),
+        ];
+        let model = Model::from_parts(vertices.clone(), Vec::new(), Vec::new(),
+                                      vec![vec![0, 1, 2, 3], vec![1, 4, 5, 2]]);
+
+        let triangles: Vec<[Vec3f; 3]> = model.triangles().map(|t| t.vertices()).collect();
+        let expected = [[0, 1, 2], [0, 2, 3], [1, 4, 5], [1, 5, 2]];
+        assert_eq!(triangles.len(), expected.len());
+        for (triangle, indices) in triangles.iter().zip(expected) {
+            for (vertex, index) in triangle.iter().zip(indices) {
+                assert_eq!((vertex.x, vertex.y, vertex.z),
+                           (vertices[index].x, vertices[index].y, vertices[index].z));
+            }
+        }
+    }
+
+    #[test]
+    fn test_smoothing_groups() {
+        let dir = temp_dir("smoothing_groups");
+        fs::write(dir.join("model.obj"), concat!(
+            "v 0 0 0\nv 1 0 0\nv 0.5 1 0\nv 0.5 0 -1\nv 0 0 1\nv 2 0 0\n",
+            "s 1\nf 1// 2// 3//\nf 2// 1// 4//\n",
+            "s 2\nf 1// 5// 3//\n",
+            "s off\nf 2// 6// 3//\n")).unwrap();
+
+        let mut model = Model::from_file(dir.join("model.obj")).unwrap();
+        model.generate_normals();
+        let faces: Vec<_> = model.iter().collect();
+        let normal = |face: usize, corner: usize| faces[face].normal(&model, corner).unwrap();
+        let close = |a: Vec3f, b: Vec3f| (a - b).norm() < 1e-9;
+
+        // the shared edge of the two faces in group 1 is smoothed
+        let smoothed = Vec3f::new(0.0, -1.0, 1.0).normalize();
+        assert!(close(normal(0, 0), smoothed));
+        assert!(close(normal(0, 1), smoothed));
+        assert!(close(normal(1, 1), smoothed));
+        assert!(close(normal(0, 2), Vec3f::new(0.0, 0.0, 1.0)));
+        assert!(close(normal(1, 2), Vec3f::new(0.0, -1.0, 0.0)));
+
+        // group 2 shares vertices with group 1 but is not averaged with it
+        let flat = Vec3f::new(-1.0, 0.5, 0.0).normalize();
+        assert!((0..3).all(|i| close(normal(2, i), flat)));
+
+        // smoothing off keeps the flat face normal
+        assert!((0..3).all(|i| close(normal(3, i), Vec3f::new(0.0, 0.0, 1.0))));
+    }
+
+    #[test]
+    fn test_compute_normals() {
+        // the five faces of an icosahedron around its top vertex, each with
+        // its own copies of the corners as in an unwelded mesh
+        let apex = Vec3f::new(0.0, 1.0, 0.0);
+        let ring: Vec<Vec3f> = (0..5)
+            .map(|i| Vec3f::from_spherical(1.0, 2.0 * std::f64::consts::PI * i as f64 / 5.0, 0.5_f64.atan()))
+            .collect();
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        for i in 0..5 {
+            faces.push(vec![vertices.len(), vertices.len() + 1, vertices.len() + 2]);
+            vertices.extend([apex, ring[i], ring[(i + 1) % 5]]);
+        }
+        let mut model = Model::from_parts(vertices, Vec::new(), Vec::new(), faces);
+
+        model.compute_normals();
+        let faces: Vec<_> = model.iter().collect();
+        let normal = |face: usize, corner: usize| faces[face].normal(&model, corner).unwrap();
+        let close = |a: Vec3f, b: Vec3f| (a - b).norm() < 1e-9;
+
+        // every face sees the same smoothed normal at the shared apex
+        assert!((0..5).all(|i| close(normal(i, 0), Vec3f::new(0.0, 1.0, 0.0))));
+        // which is stored once for all of its copies
+        assert!(faces.iter().all(|face| face.normal_indices[0] == faces[0].normal_indices[0]));
+        // one apex and five ring normals, reflected in the stats
+        assert_eq!(model.normals().len(), 6);
+        assert_eq!(model.stats().normals, 6);
+
+        // and neighbours agree on the ring vertex between them
+        for (i, face) in faces.iter().enumerate() {
+            assert!(close(normal(i, 2), normal((i + 1) % 5, 1)));
+            let [_, p2, p3] = [0, 1, 2].map(|corner| face.vertex(&model, corner));
+            let flat = (p2 - apex).cross(&(p3 - apex)).normalize();
+            assert!(!close(normal(i, 2), flat));
+        }
+
+        // smoothing groups are ignored, generate_normals leaves these faces flat
+        model.generate_normals();
+        let face = model.iter().next().unwrap();
+        let [n1, n2] = [0, 1].map(|corner| face.normal(&model, corner).unwrap());
+        assert!(close(n1, n2));
+        assert!(!close(n1, Vec3f::new(0.0, 1.0, 0.0)));        assert_eq!(model.stats().normals, 5);
+    }
+
+    #[test]
+    fn test_normals_of_degenerate_faces() {
+        let vertices = vec![
+            Vec3f::new(0.0, 0.0, 0.0),
+            Vec3f::new(1.0, 0.0, 0.0),
+            Vec3f::new(2.0, 0.0, 0.0),
+            Vec3f::new(0.0, 1.0, 0.0),
+        ];
+        let mut model = Model::from_parts(vertices, Vec::new(), Vec::new(),
+                                          vec![vec![0, 1, 2], vec![0, 1, 3]]);
+
+        // the collinear face gets a zero normal instead of NaN
+        for smooth in [false, true] {
+            if smooth { model.compute_normals() } else { model.generate_normals() }
+            assert!(model.normals().iter().all(|n| n.x.is_finite() && n.y.is_finite() && n.z.is_finite()));
+            let normal = model.iter().next().unwrap().normal(&model, 2).unwrap();
+            assert_eq!(normal.norm(), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let tetrahedron = |offset: f64| [
+            Vec3f::new(offset, 0.0, 0.0),
+            Vec3f::new(offset + 1.0, 0.0, 0.0),
+            Vec3f::new(offset, 1.0, 0.0),
+            Vec3f::new(offset, 0.0, 1.0),
+        ];
+        let vertices: Vec<Vec3f> = tetrahedron(0.0).into_iter().chain(tetrahedron(5.0)).collect();
+        let faces = |base: usize| [[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]]
+            .map(|face| face.map(|i| base + i).to_vec());
+
+        // interleave faces of both parts to make sure grouping does not rely on order
+        let [a0, a1, a2, a3] = faces(0);
+        let [b0, b1, b2, b3] = faces(4);
+        let model = Model::from_parts(vertices, Vec::new(), Vec::new(),
+                                      vec![a0, b0, a1, b1, b2, a2, b3, a3]);
+
+        let components = model.connected_components();
+        assert_eq!(components.len(), 2);
+        for (component, offset) in components.iter().zip([0.0, 5.0]) {
+            assert_eq!(component.stats().faces, 4);
+            assert_eq!(component.stats().vertices, 4);
+            assert!(component.iter()
+                .flat_map(|face| face.vertices(component))
+                .all(|v| v.x >= offset && v.x <= offset + 1.0));
+        }
+    }
+
+    #[test]
+    fn test_empty_model() {
+        let dir = temp_dir("empty_model");
+        fs::write(dir.join("model.obj"), TRIANGLE).unwrap();
+
+        let model = Model::from_file(dir.join("model.obj")).unwrap();
+        assert!(model.is_empty());
+        assert_eq!(model.stats().vertices, 3);
+
+        assert!(!Model::from_file(CUBE).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load_all() {
+        let dir = temp_dir("load_all");
+        fs::write(dir.join("model.obj"), "\
+o quad
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 1.0 0.0
+v 0.0 1.0 0.0
+vn 0.0 0.0 1.0
+f 1//1 2//1 3//1
+f 1//1 3//1 4//1
+o roof
+v 0.0 0.0 1.0
+f 2//1 3//1 5//1
+").unwrap();
+
+        let models = Model::load_all(dir.join("model.obj")).unwrap();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].stats().faces, 2);
+        assert_eq!(models[0].stats().vertices, 4);
+        assert_eq!(models[1].stats().faces, 1);
+        assert_eq!(models[1].stats().vertices, 3);
+
+        let face = models[1].iter().next().unwrap();
+        assert_eq!(face.vertex_indices, vec![0, 1, 2]);
+        let expected = [
+            Vec3f::new(1.0, 0.0, 0.0),
+            Vec3f::new(1.0, 1.0, 0.0),
+            Vec3f::new(0.0, 0.0, 1.0),
+        ];
+        for (vertex, &expected) in face.vertices(&models[1]).zip(&expected) {
+            assert!((vertex - expected).norm() < f64::EPSILON);
+        }
+
+        assert_eq!(Model::from_file(dir.join("model.obj")).unwrap().stats().faces, 3);
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let model = Model::from_file(CUBE).unwrap();
+        let adjacency = model.adjacency();
+
+        let edges: Vec<_> = adjacency.edges().collect();
+        assert_eq!(edges.len(), 18);
+        assert!(edges.iter().all(|(_, faces)| faces.len() == 2));
+
+        let face = model.iter().next().unwrap();
+        let [a, b] = [face.vertex_indices[0], face.vertex_indices[1]];
+        assert!(adjacency.edge_faces(a, b).contains(&0));
+        assert_eq!(adjacency.edge_faces(a, b), adjacency.edge_faces(b, a));
+
+        let valence: usize = (0..8).map(|v| adjacency.vertex_faces(v).len()).sum();
+        assert_eq!(valence, 36);
+
+        // cached
+        assert!(std::ptr::eq(adjacency, model.adjacency()));
+    }
+}
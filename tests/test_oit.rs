@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::framebuffer::{FrameBuffer, Rgb};
+    use tinyrs::geometry::{Triangle, Vec3f};
+    use tinyrs::oit::OitBuffer;
+    use tinyrs::renderer::Renderer;
+    use tinyrs::zbuffer::ZBuffer;
+
+    const SIZE: u32 = 16;
+
+    fn render(triangles: &[(Triangle, Vec3f)]) -> FrameBuffer {
+        let renderer = Renderer::new((SIZE, SIZE));
+        let zbuffer = ZBuffer::new((SIZE, SIZE), renderer.depth_clear_value());
+        let mut oit = OitBuffer::new((SIZE, SIZE));
+        for (triangle, color) in triangles {
+            renderer.render_triangle_transparent(&mut oit, &zbuffer, triangle, *color, 0.5).unwrap();
+        }
+
+        let mut framebuffer = FrameBuffer::new((SIZE, SIZE));
+        framebuffer.fill(Rgb::new(40, 40, 40));
+        oit.resolve(&mut framebuffer).unwrap();
+        framebuffer
+    }
+
+    #[test]
+    fn test_order_independent_blend() {
+        let red = (Triangle::new(Vec3f::new(0.0, 0.0, 1.0),
+                                 Vec3f::new(12.0, 0.0, 1.0),
+                                 Vec3f::new(0.0, 12.0, 1.0)),
+                   Vec3f::new(255.0, 0.0, 0.0));
+        let blue = (Triangle::new(Vec3f::new(2.0, 2.0, 2.0),
+                                  Vec3f::new(14.0, 2.0, 2.0),
+                                  Vec3f::new(2.0, 14.0, 2.0)),
+                    Vec3f::new(0.0, 0.0, 255.0));
+
+        let forward = render(&[red, blue]);
+        let backward = render(&[blue, red]);
+        forward.assert_eq(&backward, 0);
+
+        let at = |x: u32, y: u32| forward.pixel(x, y).unwrap();
+        let overlap = at(4, 4);
+        assert!(overlap.r > 40 && overlap.b > 40);
+        assert_eq!(overlap.r, overlap.b);
+        assert_eq!(at(1, 1), Rgb::new(147, 20, 20));
+        assert_eq!(at(15, 0), Rgb::new(40, 40, 40));
+    }
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::framebuffer::{FrameBuffer, Rgb};
+    use tinyrs::geometry::{Triangle, Vec3f};
+    use tinyrs::renderer::Renderer;
+    use tinyrs::zbuffer::ZBuffer;
+
+    const SIZE: u32 = 16;
+
+    #[test]
+    fn test_overdraw_heat() {
+        let mut renderer = Renderer::new((SIZE, SIZE));
+        assert!(renderer.overdraw().is_none());
+        renderer.set_overdraw(true);
+
+        let mut framebuffer = FrameBuffer::new((SIZE, SIZE));
+        let mut zbuffer = ZBuffer::new((SIZE, SIZE), renderer.depth_clear_value());
+
+        // three stacked triangles over the top left corner, only the first
+        // reaching further right; drawn back to front, then front to back
+        let white = [Vec3f::new(255.0, 255.0, 255.0); 3];
+        for (z, size) in [(0.0, 15.0), (0.5, 8.0), (0.2, 8.0)] {
+            let triangle = Triangle::new(Vec3f::new(0.0, 0.0, z),
+                                         Vec3f::new(size, 0.0, z),
+                                         Vec3f::new(0.0, size, z));
+            renderer.render_triangle(&mut framebuffer, &mut zbuffer, &triangle, white).unwrap();
+        }
+
+        let overdraw = renderer.overdraw().unwrap();
+        assert_eq!(overdraw.count(1, 1), Some(3));
+        assert_eq!(overdraw.count(11, 1), Some(1));
+        assert_eq!(overdraw.count(14, 14), Some(0));
+        drop(overdraw);
+
+        renderer.resolve_overdraw(&mut framebuffer).unwrap();
+        let heat = |x: u32, y: u32| {
+            let Rgb { r, g, b } = framebuffer.pixel(x, y).unwrap();
+            r as u32 + g as u32 + b as u32
+        };
+        assert!(heat(1, 1) > heat(11, 1));
+        assert!(heat(11, 1) > heat(14, 14));
+        assert_eq!(renderer.overdraw().unwrap().max(), 0);
+    }
+}
@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::palette::{distinct_color, distinct_hue, hsv_to_rgb};
+    use tinyrs::geometry::Vec3f;
+
+    const EPSILON: f64 = 1e-4_f64;
+
+    #[test]
+    fn test_distinct_hues() {
+        const COUNT: usize = 8;
+
+        for i in 0..COUNT {
+            for j in i + 1..COUNT {
+                let diff = (distinct_hue(i) - distinct_hue(j)).abs();
+                assert!(diff.min(1.0 - diff) > 0.05, "hues {} and {} are too close", i, j);
+                assert!((distinct_color(i) - distinct_color(j)).norm() > 20.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_distinct_color_values() {
+        // pinned so that changing the saturation, value or hue step shows up
+        let expected = [
+            Vec3f::new(242.25, 84.7875, 84.7875),
+            Vec3f::new(84.7875, 130.7344, 242.25),
+            Vec3f::new(176.6814, 242.25, 84.7875),
+            Vec3f::new(242.25, 84.7875, 222.6283),
+        ];
+        for (i, color) in expected.iter().enumerate() {
+            assert!((distinct_color(i) - *color).norm() < 1e-3, "color {} changed", i);
+        }
+    }
+
+    #[test]
+    fn test_hsv_to_rgb() {
+        assert!((hsv_to_rgb(0.0, 1.0, 1.0) - Vec3f::new(255.0, 0.0, 0.0)).norm() < EPSILON);
+        assert!((hsv_to_rgb(1.0 / 3.0, 1.0, 1.0) - Vec3f::new(0.0, 255.0, 0.0)).norm() < EPSILON);
+        assert!((hsv_to_rgb(2.0 / 3.0, 1.0, 1.0) - Vec3f::new(0.0, 0.0, 255.0)).norm() < EPSILON);
+        assert!((hsv_to_rgb(0.5, 0.0, 0.5) - Vec3f::new(127.5, 127.5, 127.5)).norm() < EPSILON);
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::model::{Face, Model};
+
+    const EPSILON: f64 = 1e-4_f64;
+
+    fn is_outward(model: &Model, face: &Face) -> bool {
+        let [p1, p2, p3] = [0, 1, 2].map(|i| face.vertex(model, i));
+        let normal = (p2 - p1).cross(&(p3 - p1));
+        let centroid = (p1 + p2 + p3) * (1.0 / 3.0);
+        normal.dot(&centroid) > 0.0 &&
+            (0..face.len()).all(|i| face.normal(model, i).is_some_and(|n| n.dot(&normal) > 0.0))
+    }
+
+    #[test]
+    fn test_cube() {
+        let cube = Model::cube(2.0);
+
+        assert_eq!(cube.iter().count(), 12);
+        for face in cube.iter() {
+            assert_eq!(face.len(), 3);
+            assert!(face.has_textures());
+            assert!(is_outward(&cube, face));
+            for vertex in face.vertices(&cube) {
+                assert!((vertex.x.abs() - 1.0).abs() < EPSILON ||
+                        (vertex.y.abs() - 1.0).abs() < EPSILON ||
+                        (vertex.z.abs() - 1.0).abs() < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn test_uv_sphere() {
+        let sphere = Model::uv_sphere(1.5, 16, 8);
+
+        assert_eq!(sphere.iter().count(), 16 * 2 + 16 * 6 * 2);
+        for face in sphere.iter() {
+            assert!(is_outward(&sphere, face));
+            for vertex in face.vertices(&sphere) {
+                assert!((vertex.norm() - 1.5).abs() < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn test_plane() {
+        let plane = Model::plane(4.0, 2.0, 1);
+
+        assert_eq!(plane.iter().count(), 8);
+        assert_eq!(plane.stats().vertices, 9);
+        for face in plane.iter() {
+            let [p1, p2, p3] = [0, 1, 2].map(|i| face.vertex(&plane, i));
+            assert!((p2 - p1).cross(&(p3 - p1)).z > 0.0);
+            for vertex in face.vertices(&plane) {
+                assert!(vertex.x.abs() <= 2.0 + EPSILON);
+                assert!(vertex.y.abs() <= 1.0 + EPSILON);
+            }
+        }
+    }
+}
@@ -0,0 +1,843 @@
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    #[cfg(feature = "sdl")]
+    use tinyrs::canvas::CanvasBuilder;
+    use tinyrs::common::{Point, Resolution};
+    use tinyrs::framebuffer::{FrameBuffer, RenderTarget, Rgb};
+    use tinyrs::geometry::{Mat3x3f, Mat4x4f, Triangle, Vec3f, VecUV2f};
+    use tinyrs::model::{FrontFace, Model};
+    use tinyrs::renderer::{triangle_fan, triangle_strip, CullMode, DepthFunc, DepthResolve, DiffuseModel, MissingNormalPolicy, Renderer, ShadingMode};
+    use tinyrs::scene::Transform;
+    use tinyrs::texture::Texture;
+    use tinyrs::zbuffer::{MultisampleZBuffer, ZBuffer};
+
+    const TWO_TRIANGLES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/two_triangles.obj");
+    const QUAD: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/quad.obj");
+    const COLORED_TRIANGLE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/colored_triangle.obj");
+    const TEAL_TRIANGLE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/teal_triangle.obj");
+    const TEXTURED_QUAD: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/textured_quad.obj");
+
+    const BLACK: Rgb = Rgb { r: 0, g: 0, b: 0 };
+    const WHITE: Rgb = Rgb { r: 255, g: 255, b: 255 };
+
+    const WIDTH: u32 = 16;
+    const HEIGHT: u32 = 16;
+
+    fn render(renderer: &Renderer, model: &Model) -> FrameBuffer {
+        let resolution = Resolution::new((WIDTH, HEIGHT));
+        let mut framebuffer = FrameBuffer::new(resolution);
+        let mut zbuffer = ZBuffer::new((WIDTH, HEIGHT), renderer.depth_clear_value());
+
+        let view_port = Mat4x4f::viewport(0.0, 0.0, WIDTH as f64, HEIGHT as f64);
+        renderer.render_model(&mut framebuffer, &mut zbuffer, &Vec3f::new(0.0, 0.0, 1.0),
+                              model, view_port, Mat4x4f::identity()).unwrap();
+        framebuffer
+    }
+
+    fn pixel(framebuffer: &FrameBuffer, x: u32, y: u32) -> Rgb {
+        framebuffer.pixel(x, y).unwrap()
+    }
+
+    #[test]
+    fn test_face_id_shading() {
+        let model = Model::from_file(TWO_TRIANGLES).unwrap();
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        renderer.set_shading_mode(ShadingMode::FaceId);
+
+        let first = render(&renderer, &model);
+        let second = render(&renderer, &model);
+
+        let color0 = pixel(&first, 2, 5);
+        let color1 = pixel(&first, 13, 5);
+
+        assert_ne!(color0, Rgb::default());
+        assert_ne!(color1, Rgb::default());
+        assert_ne!(color0, color1);
+
+        assert_eq!(color0, pixel(&second, 2, 5));
+        assert_eq!(color1, pixel(&second, 13, 5));
+    }
+
+    #[test]
+    fn test_depth_clear_value() {
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        assert_eq!(renderer.depth_func(), DepthFunc::Greater);
+        assert_eq!(renderer.depth_clear_value(), f64::MIN);
+        assert!(DepthFunc::Greater.passes(0.0, renderer.depth_clear_value()));
+
+        renderer.set_depth_func(DepthFunc::Less);
+        assert_eq!(renderer.depth_clear_value(), f64::MAX);
+        assert!(DepthFunc::Less.passes(0.0, renderer.depth_clear_value()));
+
+        let model = Model::from_file(TWO_TRIANGLES).unwrap();
+        let framebuffer = render(&renderer, &model);
+        assert_ne!(pixel(&framebuffer, 2, 5), Rgb::default());
+    }
+
+    #[test]
+    fn test_draw_text() {
+        let renderer = Renderer::new((WIDTH, HEIGHT));
+        let mut framebuffer = FrameBuffer::new((WIDTH, HEIGHT));
+        let white = Rgb::new(255, 255, 255);
+
+        renderer.draw_text(&mut framebuffer, 1, 2, "AB", white).unwrap();
+
+        let expected = [
+            " ###  #### ",
+            "#   # #   #",
+            "#   # #   #",
+            "#   # #### ",
+            "##### #   #",
+            "#   # #   #",
+            "#   # #### ",
+        ];
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let lit = expected.get(y.wrapping_sub(2) as usize)
+                    .and_then(|row| row.chars().nth(x.wrapping_sub(1) as usize))
+                    .is_some_and(|c| c == '#');
+                let color = if lit { white } else { Rgb::default() };
+                assert_eq!(pixel(&framebuffer, x, y), color, "pixel ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wireframe_overlay_hidden_lines() {
+        const SIZE: u32 = 32;
+
+        let vertices = vec![
+            Vec3f::new(-0.9, -0.9, -0.5),
+            Vec3f::new( 0.9, -0.9, -0.5),
+            Vec3f::new(-0.9,  0.9, -0.5),
+            Vec3f::new(-0.3, -0.3,  0.5),
+            Vec3f::new( 0.5, -0.3,  0.5),
+            Vec3f::new(-0.3,  0.5,  0.5),
+        ];
+        let normals = vec![Vec3f::new(0.0, 0.0, 1.0); 6];
+        let faces = vec![vec![0, 1, 2], vec![3, 4, 5]];
+        let model = Model::from_parts(vertices, Vec::new(), normals, faces);
+
+        let renderer = Renderer::new((SIZE, SIZE));
+        let mut framebuffer = FrameBuffer::new((SIZE, SIZE));
+        let mut zbuffer = ZBuffer::new((SIZE, SIZE), renderer.depth_clear_value());
+        let view_port = Mat4x4f::viewport(0.0, 0.0, SIZE as f64, SIZE as f64);
+        let red = Rgb::new(255, 0, 0);
+
+        renderer.render_model_wireframe_overlay(&mut framebuffer, &mut zbuffer,
+                                                &Vec3f::new(0.0, 0.0, 1.0), &model,
+                                                view_port, Mat4x4f::identity(), red).unwrap();
+
+        let at = |x: u32, y: u32| framebuffer.pixel(x, y).unwrap();
+
+        // the back triangle's diagonal edge passes behind the front triangle
+        let hidden_diagonal = (12..20u32).any(|x| (12..20)
+            .any(|y: u32| (x + y).abs_diff(SIZE) <= 1 && at(x, y) == red));
+        assert!(!hidden_diagonal);
+
+        // but is visible where nothing covers it
+        let visible_diagonal = (26..SIZE).any(|x| (0..SIZE)
+            .any(|y: u32| (x + y).abs_diff(SIZE) <= 1 && at(x, y) == red));
+        assert!(visible_diagonal);
+
+        // front triangle edges are drawn
+        assert_eq!(at(16, 11), red);
+    }
+
+    #[test]
+    fn test_render_timings() {
+        const SIZE: u32 = 64;
+
+        let model = Model::uv_sphere(0.8, 16, 8);
+        let mut renderer = Renderer::new((SIZE, SIZE));
+        renderer.set_profiling(true);
+
+        let mut framebuffer = FrameBuffer::new((SIZE, SIZE));
+        let mut zbuffer = ZBuffer::new((SIZE, SIZE), renderer.depth_clear_value());
+        let view_port = Mat4x4f::viewport(0.0, 0.0, SIZE as f64, SIZE as f64);
+        renderer.render_model(&mut framebuffer, &mut zbuffer, &Vec3f::new(0.0, 0.0, 1.0),
+                              &model, view_port, Mat4x4f::identity()).unwrap();
+
+        let timings = renderer.timings();
+        assert_eq!(timings.faces, model.iter().count());
+        assert!(timings.culled > 0);
+        assert!(timings.rasterize > Duration::ZERO);
+        assert!(timings.stages() <= timings.total);
+    }
+
+    #[test]
+    fn test_clear_between_frames() {
+        const SIZE: u32 = 32;
+
+        let model = Model::uv_sphere(0.5, 12, 6);
+        let renderer = Renderer::new((SIZE, SIZE));
+        let view_port = Mat4x4f::viewport(0.0, 0.0, SIZE as f64, SIZE as f64);
+
+        // the second frame sits behind the first, so a stale depth buffer hides it
+        let frames = [Transform::from_translation(Vec3f::new(-0.3, 0.0, 0.5)),
+                      Transform::from_translation(Vec3f::new(0.3, 0.0, 0.0))];
+
+        let render_frame = |framebuffer: &mut FrameBuffer, zbuffer: &mut ZBuffer, transform: &Transform| {
+            renderer.render_model(framebuffer, zbuffer, &Vec3f::new(0.0, 0.0, 1.0),
+                                  &model, view_port, transform.matrix()).unwrap();
+        };
+
+        let mut framebuffer = FrameBuffer::new((SIZE, SIZE));
+        let mut zbuffer = ZBuffer::new((SIZE, SIZE), renderer.depth_clear_value());
+        for transform in &frames {
+            framebuffer.clear(Rgb::default(), &mut zbuffer);
+            render_frame(&mut framebuffer, &mut zbuffer, transform);
+
+            let mut fresh = FrameBuffer::new((SIZE, SIZE));
+            let mut fresh_zbuffer = ZBuffer::new((SIZE, SIZE), renderer.depth_clear_value());
+            render_frame(&mut fresh, &mut fresh_zbuffer, transform);
+
+            framebuffer.assert_eq(&fresh, 0);
+            assert_eq!(zbuffer.data(), fresh_zbuffer.data());
+        }
+    }
+
+    #[test]
+    fn test_identity_transform_fast_path() {
+        const SIZE: u32 = 32;
+
+        let model = Model::uv_sphere(0.8, 12, 6);
+        let mut renderer = Renderer::new((SIZE, SIZE));
+        renderer.set_profiling(true);
+        let view_port = Mat4x4f::viewport(0.0, 0.0, SIZE as f64, SIZE as f64);
+
+        let render = |projection: Mat4x4f| {
+            let mut framebuffer = FrameBuffer::new((SIZE, SIZE));
+            let mut zbuffer = ZBuffer::new((SIZE, SIZE), renderer.depth_clear_value());
+            renderer.render_model(&mut framebuffer, &mut zbuffer, &Vec3f::new(0.0, 0.0, 1.0),
+                                  &model, view_port, projection).unwrap();
+            (framebuffer, renderer.timings().flops)
+        };
+
+        // scaling w along with x, y and z leaves the projected result unchanged,
+        // but is not the identity and so takes the general path
+        let scale = Mat4x4f::from([
+            2.0, 0.0, 0.0, 0.0,
+            0.0, 2.0, 0.0, 0.0,
+            0.0, 0.0, 2.0, 0.0,
+            0.0, 0.0, 0.0, 2.0,
+        ]);
+        let (general, general_flops) = render(scale);
+        let (identity, identity_flops) = render(Mat4x4f::identity());
+
+        general.assert_eq(&identity, 0);
+        assert!(identity.pixels().iter().any(|&p| p != Rgb::default()));
+        assert!(identity_flops > 0);
+        assert!(identity_flops < general_flops);
+    }
+
+    #[test]
+    #[cfg(feature = "sdl")]
+    fn test_read_pixels() {
+        // needs a display to open a window; skipped on headless machines
+        if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+            return;
+        }
+
+        let sdl_context = sdl2::init().unwrap();
+        let mut canvas = CanvasBuilder::new(&sdl_context)
+            .resolution((WIDTH, HEIGHT))
+            .build()
+            .unwrap();
+
+        let color = Rgb::new(12, 34, 56);
+        canvas.set_draw_color(color);
+        canvas.clear();
+
+        let renderer = Renderer::new((WIDTH, HEIGHT));
+        let framebuffer = renderer.read_pixels(&canvas).unwrap();
+        assert!(framebuffer.pixels().iter().all(|&p| p == color));
+    }
+
+    #[test]
+    fn test_normal_rgb_shading() {
+        let model = Model::from_file(TWO_TRIANGLES).unwrap();
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        renderer.set_shading_mode(ShadingMode::NormalRgb);
+
+        let framebuffer = render(&renderer, &model);
+        assert_eq!(pixel(&framebuffer, 2, 5), Rgb::new(128, 128, 255));
+        assert_eq!(pixel(&framebuffer, 13, 5), Rgb::new(128, 128, 255));
+    }
+
+    struct PixelLog(Vec<(u32, u32)>);
+
+    impl RenderTarget for PixelLog {
+        fn draw_pixel(&mut self, x: u32, y: u32, _color: Rgb) -> Result<(), String> {
+            self.0.push((x, y));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_render_line_clipped() {
+        let renderer = Renderer::new((WIDTH, HEIGHT));
+        let white = Rgb::new(255, 255, 255);
+
+        let mut log = PixelLog(Vec::new());
+        renderer.render_line(&mut log, Point::new(4, 8), Point::new(1000, 8), white).unwrap();
+        assert!(log.0.iter().all(|&(x, y)| x < WIDTH && y < HEIGHT));
+        assert_eq!(log.0.len(), (WIDTH - 4) as usize);
+        assert!(log.0.contains(&(WIDTH - 1, 8)));
+
+        let mut log = PixelLog(Vec::new());
+        renderer.render_line(&mut log, Point::new(8, 8), Point::new(-500, -400), white).unwrap();
+        assert!(!log.0.is_empty());
+        assert!(log.0.iter().all(|&(x, y)| x < WIDTH && y < HEIGHT));
+
+        let mut log = PixelLog(Vec::new());
+        renderer.render_line(&mut log, Point::new(-10, -10), Point::new(-1, 100), white).unwrap();
+        assert!(log.0.is_empty());
+    }
+
+    #[test]
+    fn test_triangle_budget() {
+        let model = Model::from_file(TWO_TRIANGLES).unwrap();
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        renderer.set_triangle_budget(Some(1));
+
+        let background = Rgb::default();
+        let drawn = |framebuffer: &FrameBuffer| {
+            [(2, 5), (13, 5)].map(|(x, y)| pixel(framebuffer, x, y) != background)
+        };
+
+        let first = render(&renderer, &model);
+        assert_eq!(renderer.timings().skipped, 1);
+        let second = render(&renderer, &model);
+        let third = render(&renderer, &model);
+
+        assert_eq!(drawn(&first), [true, false]);
+        assert_eq!(drawn(&second), [false, true]);
+        assert_eq!(drawn(&third), [true, false]);
+
+        renderer.set_triangle_budget(None);
+        assert_eq!(drawn(&render(&renderer, &model)), [true, true]);
+        assert_eq!(renderer.timings().skipped, 0);
+    }
+
+    #[test]
+    fn test_triangle_budget_polygons() {
+        // the quad is one face but two triangles, the triangle one more
+        let model = Model::from_parts(vec![
+            Vec3f::new(-0.8, -0.8, 0.0),
+            Vec3f::new(0.0, -0.8, 0.0),
+            Vec3f::new(0.0, 0.8, 0.0),
+            Vec3f::new(-0.8, 0.8, 0.0),
+            Vec3f::new(0.8, -0.8, 0.0),
+        ], Vec::new(), Vec::new(), vec![vec![0, 1, 2, 3], vec![1, 4, 2]]);
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+
+        renderer.set_triangle_budget(Some(2));
+        render(&renderer, &model);
+        assert_eq!(renderer.timings().skipped, 1);
+        // starting at the triangle leaves room to wrap around to the quad
+        render(&renderer, &model);
+        assert_eq!(renderer.timings().skipped, 0);
+
+        // a face is finished even when it goes over the budget
+        renderer.set_triangle_budget(Some(1));
+        render(&renderer, &model);
+        assert_eq!(renderer.timings().skipped, 1);
+    }
+
+    #[test]
+    fn test_face_caching() {
+        let mut model = Model::from_file(TWO_TRIANGLES).unwrap();
+        let uncached = render(&Renderer::new((WIDTH, HEIGHT)), &model);
+
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        assert!(!renderer.face_caching());
+        renderer.set_face_caching(true);
+
+        render(&renderer, &model).assert_eq(&uncached, 0);
+        assert_eq!(renderer.timings().cached, 0);
+        render(&renderer, &model).assert_eq(&uncached, 0);
+        assert_eq!(renderer.timings().cached, 2);
+
+        let mut framebuffer = FrameBuffer::new((WIDTH, HEIGHT));
+        let mut zbuffer = ZBuffer::new((WIDTH, HEIGHT), renderer.depth_clear_value());
+        let view_port = Mat4x4f::viewport(0.0, 0.0, WIDTH as f64, HEIGHT as f64);
+        let moved = Transform::from_translation(Vec3f::new(0.5, 0.0, 0.0)).matrix();
+        renderer.render_model(&mut framebuffer, &mut zbuffer, &Vec3f::new(0.0, 0.0, 1.0),
+                              &model, view_port, moved).unwrap();
+        assert_eq!(renderer.timings().cached, 0);
+
+        // an edit in place invalidates the cache, as does a new model of the
+        // same size
+        render(&renderer, &model);
+        model.normalize_to_unit_cube();
+        render(&renderer, &model);
+        assert_eq!(renderer.timings().cached, 0);
+        let other = Model::from_file(TWO_TRIANGLES).unwrap();
+        assert_ne!(other.generation(), model.generation());
+        render(&renderer, &other);
+        assert_eq!(renderer.timings().cached, 0);
+    }
+
+    #[test]
+    fn test_line_depth_fade() {
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        renderer.set_line_depth_fade(Some((1.0, -1.0)));
+        let mut framebuffer = FrameBuffer::new((WIDTH, HEIGHT));
+        let zbuffer = ZBuffer::new((WIDTH, HEIGHT), renderer.depth_clear_value());
+
+        renderer.render_line_depth_tested(&mut framebuffer, &zbuffer, Vec3f::new(1.0, 8.0, 1.0),
+                                          Vec3f::new(14.0, 8.0, -1.0), Rgb::new(255, 255, 255)).unwrap();
+
+        let brightness: Vec<u8> = (1..14).map(|x| pixel(&framebuffer, x, 8).r).collect();
+        assert_eq!(brightness[0], 255);
+        assert!(brightness.windows(2).all(|pair| pair[1] < pair[0]));
+    }
+
+    #[test]
+    fn test_degenerate_face_skipped() {
+        let vertices = vec![
+            Vec3f::new(-1.0, -1.0, 0.0),
+            Vec3f::new(0.0, 0.0, 0.0),
+            Vec3f::new(1.0, 1.0, 0.0),
+        ];
+        let model = Model::from_parts(vertices, Vec::new(), Vec::new(), vec![vec![0, 1, 2]]);
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        renderer.set_profiling(true);
+
+        let framebuffer = render(&renderer, &model);
+        assert!(framebuffer.pixels().iter().all(|&pixel| pixel == Rgb::default()));
+        assert_eq!(renderer.timings().culled, 1);
+    }
+
+    #[test]
+    fn test_per_model_front_face() {
+        let triangle = |offset: f64, indices: Vec<usize>| {
+            let vertices = vec![
+                Vec3f::new(offset - 0.25, -0.5, 0.0),
+                Vec3f::new(offset + 0.25, -0.5, 0.0),
+                Vec3f::new(offset - 0.25, 0.5, 0.0),
+            ];
+            let normals = vec![Vec3f::new(0.0, 0.0, 1.0); 3];
+            Model::from_parts(vertices, Vec::new(), normals, vec![indices])
+        };
+        let counter_clockwise = triangle(-0.5, vec![0, 1, 2]);
+        let mut clockwise = triangle(0.5, vec![0, 2, 1]);
+
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        renderer.set_cull_mode(CullMode::Back);
+        let drawn = |renderer: &Renderer, model: &Model, x: u32| {
+            pixel(&render(renderer, model), x, 7) != Rgb::default()
+        };
+
+        assert!(drawn(&renderer, &counter_clockwise, 3));
+        assert!(!drawn(&renderer, &clockwise, 11));
+
+        clockwise.set_front_face(FrontFace::Clockwise);
+        assert!(drawn(&renderer, &counter_clockwise, 3));
+        assert!(drawn(&renderer, &clockwise, 11));
+
+        renderer.set_cull_mode(CullMode::Front);
+        assert!(!drawn(&renderer, &counter_clockwise, 3));
+        assert!(!drawn(&renderer, &clockwise, 11));
+    }
+
+    #[test]
+    fn test_axis_gizmo() {
+        const SIZE: u32 = 32;
+
+        // turned so that none of the axes points straight at the viewer
+        let (sin_y, cos_y) = 0.5_f64.sin_cos();
+        let (sin_x, cos_x) = 0.4_f64.sin_cos();
+        let rotation = Mat3x3f::from([
+            1.0, 0.0, 0.0,
+            0.0, cos_x, -sin_x,
+            0.0, sin_x, cos_x,
+        ]) * Mat3x3f::from([
+            cos_y, 0.0, sin_y,
+            0.0, 1.0, 0.0,
+            -sin_y, 0.0, cos_y,
+        ]);
+
+        let renderer = Renderer::new((SIZE, SIZE));
+        let mut framebuffer = FrameBuffer::new((SIZE, SIZE));
+        let origin = Point::new(16, 16);
+        renderer.render_axis_gizmo(&mut framebuffer, rotation, origin, 10.0).unwrap();
+
+        let axes = [
+            (Vec3f::new(1.0, 0.0, 0.0), Rgb::new(255, 0, 0)),
+            (Vec3f::new(0.0, 1.0, 0.0), Rgb::new(0, 255, 0)),
+            (Vec3f::new(0.0, 0.0, 1.0), Rgb::new(0, 0, 255)),
+        ];
+        for (axis, color) in axes {
+            let end = rotation * axis * 10.0;
+            let at = |t: f64| {
+                let x = (origin.x as f64 + end.x * t).round() as u32;
+                let y = (origin.y as f64 + end.y * t).round() as u32;
+                (x, y)
+            };
+            // Bresenham may step a pixel off the rounded position
+            let near = |(x, y): (u32, u32)| (x - 1..=x + 1)
+                .any(|x| (y - 1..=y + 1).any(|y| framebuffer.pixel(x, y) == Some(color)));
+            let (x, y) = at(1.0);
+            assert_eq!(framebuffer.pixel(x, y), Some(color));
+            assert!(near(at(0.5)));
+            assert!(near(at(0.0)));
+        }
+
+        let origin_color = framebuffer.pixel(16, 16).unwrap();
+        assert!(axes.iter().any(|&(_, color)| color == origin_color));
+        assert!(framebuffer.pixels().iter()
+            .all(|&pixel| pixel == Rgb::default() || axes.iter().any(|&(_, color)| color == pixel)));
+    }
+
+    #[test]
+    fn test_screen_bounds() {
+        const SIZE: u32 = 64;
+
+        let renderer = Renderer::new((SIZE, SIZE));
+        let view_port = Mat4x4f::viewport(0.0, 0.0, SIZE as f64, SIZE as f64);
+        let camera = Mat4x4f::from([
+            1.0, 0.0,  0.0,       0.0,
+            0.0, 1.0,  0.0,       0.0,
+            0.0, 0.0,  1.0,       0.0,
+            0.0, 0.0, -1.0 / 3.0, 1.0,
+        ]);
+
+        let (min, max) = renderer.screen_bounds(&Model::cube(1.0), view_port, camera).unwrap();
+        assert!(min.x > 0 && min.y > 0);
+        assert!(max.x < SIZE as i32 && max.y < SIZE as i32);
+        assert!(((min.x + max.x) - SIZE as i32).abs() <= 1);
+        assert!(((min.y + max.y) - SIZE as i32).abs() <= 1);
+
+        // everything behind the camera
+        let behind = Model::from_parts(vec![Vec3f::new(0.0, 0.0, 5.0)], Vec::new(), Vec::new(), Vec::new());
+        assert!(renderer.screen_bounds(&behind, view_port, camera).is_none());
+    }
+
+    #[test]
+    fn test_render_triangle_lit() {
+        let renderer = Renderer::new((WIDTH, HEIGHT));
+        let mut framebuffer = FrameBuffer::new((WIDTH, HEIGHT));
+        let mut zbuffer = ZBuffer::new((WIDTH, HEIGHT), renderer.depth_clear_value());
+
+        let triangle = Triangle::new(Vec3f::new(0.0, 0.0, 0.0),
+                                     Vec3f::new(15.0, 0.0, 0.0),
+                                     Vec3f::new(0.0, 15.0, 0.0));
+        let red = Vec3f::new(255.0, 0.0, 0.0);
+        renderer.render_triangle_lit(&mut framebuffer, &mut zbuffer, &triangle,
+                                     [red; 3], [0.5; 3], [1.0; 3]).unwrap();
+
+        assert_eq!(pixel(&framebuffer, 3, 3), Rgb::new(127, 0, 0));
+    }
+
+    #[test]
+    fn test_triangle_strip() {
+        let vertices = [
+            Vec3f::new(0.0, 0.0, 0.0),
+            Vec3f::new(0.0, 15.0, 0.0),
+            Vec3f::new(8.0, 0.0, 0.0),
+            Vec3f::new(8.0, 15.0, 0.0),
+        ];
+        let triangles: Vec<_> = triangle_strip(vertices.len()).collect();
+        assert_eq!(triangles, vec![[0, 1, 2], [2, 1, 3]]);
+
+        let winding = |[a, b, c]: [usize; 3]| {
+            let (u, v) = (vertices[b] - vertices[a], vertices[c] - vertices[a]);
+            (u.x * v.y - u.y * v.x).signum()
+        };
+        assert_eq!(winding(triangles[0]), winding(triangles[1]));
+
+        let renderer = Renderer::new((WIDTH, HEIGHT));
+        let mut framebuffer = FrameBuffer::new((WIDTH, HEIGHT));
+        let mut zbuffer = ZBuffer::new((WIDTH, HEIGHT), renderer.depth_clear_value());
+        let white = Vec3f::new(255.0, 255.0, 255.0);
+        renderer.render_triangle_strip(&mut framebuffer, &mut zbuffer, &vertices, &[white; 4]).unwrap();
+
+        assert_eq!(pixel(&framebuffer, 2, 2), Rgb::new(255, 255, 255));
+        assert_eq!(pixel(&framebuffer, 6, 13), Rgb::new(255, 255, 255));
+        assert_eq!(pixel(&framebuffer, 12, 8), Rgb::default());
+
+        assert!(renderer.render_triangle_strip(&mut framebuffer, &mut zbuffer, &vertices, &[white; 3]).is_err());
+        assert_eq!(triangle_fan(5).collect::<Vec<_>>(), vec![[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+    }
+
+    #[test]
+    fn test_render_line_aa() {
+        let renderer = Renderer::new((WIDTH, HEIGHT));
+        let mut framebuffer = FrameBuffer::new((WIDTH, HEIGHT));
+        let white = Rgb::new(255, 255, 255);
+        renderer.render_line_aa(&mut framebuffer, Vec3f::new(1.0, 2.0, 0.0),
+                                Vec3f::new(14.0, 7.0, 0.0), white);
+
+        // the ideal line passes x = 5 at y = 3.54, between rows 3 and 4
+        let above = pixel(&framebuffer, 5, 3).r;
+        let below = pixel(&framebuffer, 5, 4).r;
+        assert!(above > 0 && above < 255);
+        assert!(below > 0 && below < 255);
+        assert!(below > above);
+        assert!((above as i32 + below as i32 - 255).abs() <= 1);
+        assert_eq!(pixel(&framebuffer, 5, 2), Rgb::default());
+        assert_eq!(pixel(&framebuffer, 5, 5), Rgb::default());
+    }
+
+    #[test]
+    fn test_diffuse_model() {
+        assert_eq!(DiffuseModel::Lambert.intensity(0.0), 0.0);
+        assert!(DiffuseModel::HalfLambert.intensity(0.0) > 0.0);
+        assert_eq!(DiffuseModel::HalfLambert.intensity(1.0), 1.0);
+
+        // tilted by 60 degrees away from the light, dot = 0.5
+        let (sin, cos) = 60.0_f64.to_radians().sin_cos();
+        let vertices = vec![
+            Vec3f::new(-1.0, -1.0, 0.0),
+            Vec3f::new(1.0, -1.0, 0.0),
+            Vec3f::new(-1.0, -1.0 + 2.0 * cos, 2.0 * sin),
+        ];
+        let model = Model::from_parts(vertices, Vec::new(), Vec::new(), vec![vec![0, 1, 2]]);
+
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        assert_eq!(renderer.diffuse_model(), DiffuseModel::Lambert);
+        assert_eq!(pixel(&render(&renderer, &model), 2, 2), Rgb::new(127, 127, 127));
+        renderer.set_diffuse_model(DiffuseModel::HalfLambert);
+        assert_eq!(pixel(&render(&renderer, &model), 2, 2), Rgb::new(143, 143, 143));
+    }
+
+    #[test]
+    fn test_half_lambert_past_terminator() {
+        assert!(!DiffuseModel::Lambert.wraps());
+        assert!(DiffuseModel::HalfLambert.wraps());
+
+        // facing the camera, but with normals turned away from the light,
+        // dot = -0.6
+        let vertices = vec![
+            Vec3f::new(-1.0, -1.0, 0.0),
+            Vec3f::new(1.0, -1.0, 0.0),
+            Vec3f::new(-1.0, 1.0, 0.0),
+        ];
+        let normals = vec![Vec3f::new(0.8, 0.0, -0.6); 3];
+        let model = Model::from_parts(vertices, Vec::new(), normals, vec![vec![0, 1, 2]]);
+
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        assert_eq!(pixel(&render(&renderer, &model), 2, 2), Rgb::default());
+        renderer.set_diffuse_model(DiffuseModel::HalfLambert);
+        assert_eq!(pixel(&render(&renderer, &model), 2, 2), Rgb::new(10, 10, 10));
+    }
+
+    #[test]
+    fn test_missing_normal_policy() {
+        // tilted by 60 degrees away from the light, so the geometric normal
+        // gives an intensity of 0.5
+        let (sin, cos) = 60.0_f64.to_radians().sin_cos();
+        let vertices = vec![
+            Vec3f::new(-1.0, -1.0, 0.0),
+            Vec3f::new(1.0, -1.0, 0.0),
+            Vec3f::new(-1.0, -1.0 + 2.0 * cos, 2.0 * sin),
+        ];
+        let model = Model::from_parts(vertices, Vec::new(), Vec::new(), vec![vec![0, 1, 2]]);
+
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        assert!(matches!(renderer.missing_normal_policy(), MissingNormalPolicy::FlatGeometric));
+        assert_eq!(pixel(&render(&renderer, &model), 2, 2), Rgb::new(127, 127, 127));
+
+        renderer.set_missing_normal_policy(MissingNormalPolicy::SolidColor(Vec3f::new(0.0, 200.0, 0.0)));
+        assert_eq!(pixel(&render(&renderer, &model), 2, 2), Rgb::new(0, 200, 0));
+
+        renderer.set_missing_normal_policy(MissingNormalPolicy::Rainbow);
+        let rainbow = pixel(&render(&renderer, &model), 2, 2);
+        assert!(rainbow.r > rainbow.g && rainbow.r > rainbow.b);
+    }
+
+    #[test]
+    fn test_depth_resolve() {
+        let mut renderer = Renderer::default();
+        let mut samples = MultisampleZBuffer::new((WIDTH, HEIGHT), 4, renderer.depth_clear_value());
+
+        // a far background everywhere and a nearer triangle whose left edge
+        // at x = 5.2 splits the samples of column 5
+        let background = Triangle::new(Vec3f::new(-20.0, -20.0, 1.0), Vec3f::new(60.0, -20.0, 1.0),
+                                       Vec3f::new(-20.0, 60.0, 1.0));
+        let front = Triangle::new(Vec3f::new(5.2, -20.0, 5.0), Vec3f::new(60.0, -20.0, 5.0),
+                                  Vec3f::new(5.2, 60.0, 5.0));
+        renderer.render_triangle_depth_multisample(&mut samples, &front);
+
+        // only part of the edge pixel is covered, the rest stays clear
+        let edge = samples.pixel(5, 8).unwrap().to_vec();
+        let covered: Vec<f64> = edge.iter().copied().filter(|&z| z != samples.clear_value()).collect();
+        assert!(!covered.is_empty() && covered.len() < edge.len());
+
+        let mut zbuffer = ZBuffer::new((WIDTH, HEIGHT), renderer.depth_clear_value());
+        for policy in [DepthResolve::Nearest, DepthResolve::Average] {
+            renderer.set_depth_resolve(policy);
+            renderer.resolve_depth(&samples, &mut zbuffer).unwrap();
+            assert_eq!(zbuffer.depth(5, 8), Some(5.0));
+            assert_eq!(zbuffer.depth(0, 8), Some(zbuffer.clear_value()));
+        }
+
+        renderer.render_triangle_depth_multisample(&mut samples, &background);
+        let edge = samples.pixel(5, 8).unwrap().to_vec();
+        assert!(edge.contains(&1.0) && edge.contains(&5.0));
+
+        renderer.set_depth_resolve(DepthResolve::Nearest);
+        renderer.resolve_depth(&samples, &mut zbuffer).unwrap();
+        assert_eq!(zbuffer.depth(5, 8), Some(5.0));
+        assert_eq!(zbuffer.depth(0, 8), Some(1.0));
+
+        renderer.set_depth_resolve(DepthResolve::Average);
+        renderer.resolve_depth(&samples, &mut zbuffer).unwrap();
+        let average = edge.iter().sum::<f64>() / edge.len() as f64;
+        assert!((zbuffer.depth(5, 8).unwrap() - average).abs() < 1e-12);
+        assert!(average > 1.0 && average < 5.0);
+
+        let mut small = ZBuffer::new((4, 4), renderer.depth_clear_value());
+        assert!(renderer.resolve_depth(&samples, &mut small).is_err());
+    }
+
+    #[test]
+    fn test_shading_mode_cycle() {
+        assert_eq!(ShadingMode::Gouraud.next(), ShadingMode::FaceId);
+        assert_eq!(ShadingMode::FaceId.next(), ShadingMode::NormalRgb);
+        assert_eq!(ShadingMode::NormalRgb.next(), ShadingMode::Gouraud);
+
+        let mut mode = ShadingMode::default();
+        for _ in 0..ShadingMode::ALL.len() {
+            mode = mode.next();
+        }
+        assert_eq!(mode, ShadingMode::default());
+    }
+
+    #[test]
+    fn test_polygon_faces() {
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        let lit = |framebuffer: &FrameBuffer| {
+            [(3, 3), (12, 3), (3, 12), (12, 12), (8, 8)]
+                .iter()
+                .all(|&(x, y)| pixel(framebuffer, x, y) != Rgb::default())
+        };
+
+        // both halves of the quad are drawn
+        let quad = Model::from_file(QUAD).unwrap();
+        assert!(lit(&render(&renderer, &quad)));
+
+        // a pentagon whose first fan triangle is collinear still draws the rest
+        let pentagon = Model::from_parts(vec![
+            Vec3f::new(-0.8, -0.8, 0.0),
+            Vec3f::new(0.0, -0.8, 0.0),
+            Vec3f::new(0.8, -0.8, 0.0),
+            Vec3f::new(0.8, 0.8, 0.0),
+            Vec3f::new(-0.8, 0.8, 0.0),
+        ], Vec::new(), Vec::new(), vec![vec![0, 1, 2, 3, 4]]);
+        renderer.set_profiling(true);
+        assert!(lit(&render(&renderer, &pentagon)));
+        assert_eq!(renderer.timings().culled, 1);
+
+        // a face seen edge-on is dropped rather than rasterized
+        let edge_on = Model::from_parts(vec![
+            Vec3f::new(-0.8, 0.0, -0.5),
+            Vec3f::new(0.8, 0.0, -0.5),
+            Vec3f::new(0.0, 0.0, 0.5),
+        ], Vec::new(), Vec::new(), vec![vec![0, 1, 2]]);
+        render(&renderer, &edge_on);
+        assert_eq!(renderer.timings().culled, 1);
+    }
+
+    #[test]
+    fn test_material_diffuse_color() {
+        let model = Model::from_file(TEAL_TRIANGLE).unwrap();
+        let renderer = Renderer::new((WIDTH, HEIGHT));
+        assert_eq!(pixel(&render(&renderer, &model), 2, 2), Rgb::new(0, 127, 255));
+    }
+
+    #[test]
+    fn test_vertex_colors() {
+        let model = Model::from_file(COLORED_TRIANGLE).unwrap();
+        let framebuffer = render(&Renderer::new((WIDTH, HEIGHT)), &model);
+
+        // each corner is dominated by its own vertex color
+        let red = pixel(&framebuffer, 1, 1);
+        let green = pixel(&framebuffer, 13, 1);
+        let blue = pixel(&framebuffer, 1, 13);
+        assert!(red.r > red.g && red.r > red.b);
+        assert!(green.g > green.r && green.g > green.b);
+        assert!(blue.b > blue.r && blue.b > blue.g);
+    }
+
+    // white in the top left and bottom right texel, black elsewhere
+    fn checker() -> Texture {
+        Texture::new((2, 2), vec![WHITE, BLACK, BLACK, WHITE]).unwrap()
+    }
+
+    #[test]
+    fn test_render_textured_triangle() {
+        let renderer = Renderer::new((WIDTH, HEIGHT));
+        let mut framebuffer = FrameBuffer::new((WIDTH, HEIGHT));
+        let mut zbuffer = ZBuffer::new((WIDTH, HEIGHT), renderer.depth_clear_value());
+
+        let triangle = Triangle::new(Vec3f::new(0.0, 0.0, 0.0),
+                                     Vec3f::new(15.0, 0.0, 0.0),
+                                     Vec3f::new(0.0, 15.0, 0.0));
+        let uvs = [VecUV2f::new(0.0, 0.0), VecUV2f::new(1.0, 0.0), VecUV2f::new(0.0, 1.0)];
+        renderer.render_textured_triangle(&mut framebuffer, &mut zbuffer, &triangle, &checker(),
+                                          uvs, [1.0; 3], [1.0; 3]).unwrap();
+
+        assert_eq!(pixel(&framebuffer, 1, 1), BLACK);
+        assert_eq!(pixel(&framebuffer, 13, 1), WHITE);
+        assert_eq!(pixel(&framebuffer, 1, 13), WHITE);
+
+        let mut framebuffer = FrameBuffer::new((WIDTH, HEIGHT));
+        let mut zbuffer = ZBuffer::new((WIDTH, HEIGHT), renderer.depth_clear_value());
+        renderer.render_textured_triangle(&mut framebuffer, &mut zbuffer, &triangle, &checker(),
+                                          uvs, [0.5; 3], [1.0; 3]).unwrap();
+        assert_eq!(pixel(&framebuffer, 13, 1), Rgb::new(127, 127, 127));
+    }
+
+    #[test]
+    fn test_textured_faces() {
+        let vertices = vec![
+            Vec3f::new(-1.0, -1.0, 0.0),
+            Vec3f::new(1.0, -1.0, 0.0),
+            Vec3f::new(-1.0, 1.0, 0.0),
+        ];
+        let textures = vec![VecUV2f::new(0.0, 0.0), VecUV2f::new(1.0, 0.0), VecUV2f::new(0.0, 1.0)];
+        let normals = vec![Vec3f::new(0.0, 0.0, 1.0); 3];
+        let model = Model::from_parts(vertices, textures, normals, vec![vec![0, 1, 2]]);
+
+        // without a texture the face keeps its material color
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        let plain = render(&renderer, &model);
+        assert_eq!(pixel(&plain, 1, 1), pixel(&plain, 13, 1));
+
+        renderer.set_default_texture(Some(checker()));
+        let framebuffer = render(&renderer, &model);
+        let white = pixel(&framebuffer, 13, 1);
+        assert_eq!(pixel(&framebuffer, 1, 1), BLACK);
+        assert!(white.r > 0 && white.r == white.g && white.g == white.b);
+        assert_eq!(pixel(&framebuffer, 1, 13), white);
+    }
+
+    #[test]
+    fn test_material_textures() {
+        let model = Model::from_file(TEXTURED_QUAD).unwrap();
+        let red = Rgb::new(255, 0, 0);
+
+        let mut renderer = Renderer::new((WIDTH, HEIGHT));
+        renderer.set_material_texture(0, Some(checker()));
+        renderer.set_material_texture(1, Some(Texture::new((1, 1), vec![red]).unwrap()));
+        assert!(renderer.material_texture(1).is_some());
+
+        // each triangle gets the texture of its own material
+        let framebuffer = render(&renderer, &model);
+        assert_eq!(pixel(&framebuffer, 1, 1), BLACK);
+        assert_eq!(pixel(&framebuffer, 13, 1), WHITE);
+        assert_eq!(pixel(&framebuffer, 14, 14), red);
+
+        // the default only fills in for materials without a texture
+        renderer.set_material_texture(1, None);
+        renderer.set_default_texture(Some(Texture::new((1, 1), vec![BLACK]).unwrap()));
+        let framebuffer = render(&renderer, &model);
+        assert_eq!(pixel(&framebuffer, 13, 1), WHITE);
+        assert_eq!(pixel(&framebuffer, 14, 14), BLACK);
+    }
+}
@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::framebuffer::{FrameBuffer, Rgb};
+    use tinyrs::geometry::{Mat4x1f, Mat4x4f, Vec3f};
+    use tinyrs::model::Model;
+    use tinyrs::renderer::Renderer;
+    use tinyrs::scene::{Camera, Node, PerspectiveCamera, Transform};
+    use tinyrs::shadow::Light;
+    use tinyrs::zbuffer::ZBuffer;
+
+    const EPSILON: f64 = 1e-4_f64;
+
+    fn hierarchy() -> Node {
+        let mut parent = Node::new(Transform::from_translation(Vec3f::new(1.0, 0.0, 0.0)), None);
+        parent.add_child(Node::new(Transform::from_translation(Vec3f::new(0.0, 2.0, 0.0)), None));
+        parent
+    }
+
+    #[test]
+    fn test_child_world_position() {
+        let parent = hierarchy();
+
+        let parent_world = parent.world_transform(Mat4x4f::identity());
+        let child_world = parent.children[0].world_transform(parent_world);
+
+        let origin = Vec3f::from(child_world * Mat4x1f::from(Vec3f::new(0.0, 0.0, 0.0)));
+        assert!((origin - Vec3f::new(1.0, 2.0, 0.0)).norm() < EPSILON);
+    }
+
+    #[test]
+    fn test_transform_order() {
+        let transform = Transform {
+            translation: Vec3f::new(1.0, 0.0, 0.0),
+            rotation: Vec3f::new(0.0, 0.0, std::f64::consts::FRAC_PI_2),
+            scale: Vec3f::new(2.0, 2.0, 2.0),
+        };
+
+        let res = Vec3f::from(transform.matrix() * Mat4x1f::from(Vec3f::new(1.0, 0.0, 0.0)));
+        assert!((res - Vec3f::new(1.0, 2.0, 0.0)).norm() < EPSILON);
+    }
+
+    #[test]
+    fn test_render_hierarchy() {
+        const SIZE: u32 = 16;
+
+        let mut parent = Node::new(Transform::from_translation(Vec3f::new(-0.5, 0.0, 0.0)), None);
+        parent.add_child(Node::new(Transform::from_translation(Vec3f::new(1.0, 0.0, 0.0)),
+                                   Some(Model::plane(0.5, 0.5, 0))));
+
+        let renderer = Renderer::new((SIZE, SIZE));
+        let mut framebuffer = FrameBuffer::new((SIZE, SIZE));
+        let mut zbuffer = ZBuffer::new((SIZE, SIZE), renderer.depth_clear_value());
+        let camera = Camera {
+            view_port: Mat4x4f::viewport(0.0, 0.0, SIZE as f64, SIZE as f64),
+            projection: Mat4x4f::identity(),
+        };
+        let light = Light::new(Vec3f::new(0.0, 0.0, 1.0));
+
+        parent.render(Mat4x4f::identity(), &renderer, &mut framebuffer, &mut zbuffer,
+                      &camera, &[light]).unwrap();
+
+        let at = |x: u32, y: u32| framebuffer.pixel(x, y).unwrap();
+        assert_eq!(at(12, 8), Rgb::new(255, 255, 255));
+        assert_eq!(at(4, 8), Rgb::default());
+    }
+
+    #[test]
+    fn test_render_rotated_node() {
+        const SIZE: u32 = 16;
+
+        // turned 60 degrees away from the light, dot = 0.5
+        let transform = Transform {
+            rotation: Vec3f::new(0.0, std::f64::consts::FRAC_PI_3, 0.0),
+            ..Default::default()
+        };
+        let node = Node::new(transform, Some(Model::plane(0.5, 0.5, 0)));
+
+        let renderer = Renderer::new((SIZE, SIZE));
+        let camera = Camera {
+            view_port: Mat4x4f::viewport(0.0, 0.0, SIZE as f64, SIZE as f64),
+            projection: Mat4x4f::identity(),
+        };
+        let render = |lights: &[Light]| {
+            let mut framebuffer = FrameBuffer::new((SIZE, SIZE));
+            let mut zbuffer = ZBuffer::new((SIZE, SIZE), renderer.depth_clear_value());
+            node.render(Mat4x4f::identity(), &renderer, &mut framebuffer, &mut zbuffer,
+                        &camera, lights).unwrap();
+            framebuffer.pixel(8, 8).unwrap()
+        };
+
+        let light = Light::new(Vec3f::new(0.0, 0.0, 1.0));
+        assert_eq!(render(&[light]), Rgb::new(127, 127, 127));
+
+        // a second light along the rotated normal adds its full intensity
+        let (sin, cos) = std::f64::consts::FRAC_PI_3.sin_cos();
+        let normal = Vec3f::new(sin, 0.0, cos);
+        let second = Light::new(normal * 0.25);
+        assert_eq!(render(&[light, second]), Rgb::new(191, 191, 191));
+        assert_eq!(render(&[]), Rgb::default());
+    }
+
+    #[test]
+    fn test_near_plane() {
+        let mut camera = PerspectiveCamera::new(0.5);
+        camera.clamp_distance(1.0);
+        assert!((camera.distance - (1.0 + camera.near)).abs() < EPSILON);
+
+        let projection = camera.projection(4.0 / 3.0);
+        let in_front = Vec3f::new(0.5, 0.5, camera.distance - camera.near * (1.0 + 1e-9));
+        let clip = projection * Mat4x1f::from(in_front);
+        assert!(clip[3][0] > 0.0);
+
+        let ndc = Vec3f::from(clip);
+        assert!(ndc.x.is_finite() && ndc.y.is_finite() && ndc.z.is_finite());
+        assert!((ndc.z + 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_orbit() {
+        let mut camera = PerspectiveCamera::new(4.0);
+        camera.orbit(std::f64::consts::FRAC_PI_2, 0.0);
+        let eye = camera.eye();
+        assert!((eye.x - 4.0).abs() < EPSILON && eye.z.abs() < EPSILON);
+
+        // the eye sits at the view-space origin whichever way it is turned
+        camera.orbit(0.3, 0.4);
+        let eye = Vec3f::from(camera.view() * Mat4x1f::from(camera.eye()));
+        assert!(eye.norm() < EPSILON);
+
+        // elevation stops short of the poles
+        camera.orbit(0.0, 10.0);
+        assert!(camera.elevation < std::f64::consts::FRAC_PI_2);
+        assert!(camera.view()[0][0].is_finite());
+    }
+
+    #[test]
+    fn test_headlight() {
+        let mut camera = PerspectiveCamera::new(4.0);
+        let light = camera.headlight(Mat4x4f::identity());
+        assert!((light - Vec3f::new(0.0, 0.0, 1.0)).norm() < EPSILON);
+
+        // follows the camera around to the far side of the model
+        camera.orbit(std::f64::consts::PI, 0.0);
+        let light = camera.headlight(Mat4x4f::identity());
+        assert!((light - Vec3f::new(0.0, 0.0, -1.0)).norm() < EPSILON);
+
+        // and is expressed in the model's own space, undoing its rotation
+        let turned = Mat4x4f::rotation_y(std::f64::consts::PI);
+        let light = camera.headlight(turned);
+        assert!((light - Vec3f::new(0.0, 0.0, 1.0)).norm() < EPSILON);
+    }
+}
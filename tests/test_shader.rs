@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::framebuffer::Rgb;
+    use tinyrs::geometry::{Vec3f, VecUV2f};
+    use tinyrs::shader::{Shader, Varyings};
+
+    const EPSILON: f64 = 1e-4_f64;
+
+    fn varyings() -> [Varyings; 3] {
+        [
+            Varyings {
+                color: Vec3f::new(255.0, 0.0, 0.0),
+                normal: Vec3f::new(1.0, 0.0, 0.0),
+                uv: VecUV2f::new(0.0, 0.0),
+            },
+            Varyings {
+                color: Vec3f::new(0.0, 255.0, 0.0),
+                normal: Vec3f::new(0.0, 1.0, 0.0),
+                uv: VecUV2f::new(1.0, 0.0),
+            },
+            Varyings {
+                color: Vec3f::new(0.0, 0.0, 255.0),
+                normal: Vec3f::new(0.0, 0.0, 1.0),
+                uv: VecUV2f::new(0.0, 1.0),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_interpolate_centroid() {
+        let third = 1.0 / 3.0;
+        let res = Varyings::interpolate(&varyings(), [third; 3], [1.0; 3]);
+
+        assert!((res.color - Vec3f::new(85.0, 85.0, 85.0)).norm() < EPSILON);
+        assert!((res.normal - Vec3f::new(third, third, third)).norm() < EPSILON);
+        assert!((res.uv.u - third).abs() < EPSILON);
+        assert!((res.uv.v - third).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_interpolate_perspective_correct() {
+        // the first vertex is twice as far away, so it contributes half as much
+        let res = Varyings::interpolate(&varyings(), [0.5, 0.5, 0.0], [0.5, 1.0, 1.0]);
+
+        assert!((res.uv.u - 2.0 / 3.0).abs() < EPSILON);
+        assert!((res.color - Vec3f::new(85.0, 170.0, 0.0)).norm() < EPSILON);
+    }
+
+    #[test]
+    fn test_closure_shader() {
+        let shader = |varyings: &Varyings| Rgb::from(varyings.color);
+        let res = Varyings::interpolate(&varyings(), [1.0, 0.0, 0.0], [1.0; 3]);
+
+        assert_eq!(shader.fragment(&res), Rgb::new(255, 0, 0));
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::geometry::{Mat4x4f, Vec3f};
+    use tinyrs::model::Model;
+    use tinyrs::renderer::Renderer;
+    use tinyrs::shadow::{Light, ShadowMap};
+
+    const EPSILON: f64 = 1e-4_f64;
+    const SIZE: u32 = 8;
+
+    // an occluder covering the left part of the light's view, made of
+    // `faces` over its four corners
+    fn occluded_shadow_map(faces: Vec<Vec<usize>>) -> ShadowMap {
+        let vertices = vec![
+            Vec3f::new(-1.0,  -1.0, 0.5),
+            Vec3f::new(-0.25, -1.0, 0.5),
+            Vec3f::new(-0.25,  1.0, 0.5),
+            Vec3f::new(-1.0,   1.0, 0.5),
+        ];
+        let occluder = Model::from_parts(vertices, Vec::new(), Vec::new(), faces);
+
+        let transform = Mat4x4f::viewport(0.0, 0.0, SIZE as f64, SIZE as f64);
+        let mut shadow_map = ShadowMap::new((SIZE, SIZE), transform);
+        shadow_map.render(&occluder);
+        shadow_map
+    }
+
+    fn shadow_map() -> ShadowMap {
+        occluded_shadow_map(vec![vec![0, 1, 2], vec![0, 2, 3]])
+    }
+
+    #[test]
+    fn test_hard_shadow() {
+        let shadow_map = shadow_map();
+        let renderer = Renderer::new((SIZE, SIZE));
+        let light = Light::new(Vec3f::new(0.0, 0.0, 1.0));
+
+        let shadowed = Vec3f::new(-0.5, 0.0, 0.0);
+        let lit = Vec3f::new(0.5, 0.0, 0.0);
+        let above = Vec3f::new(-0.5, 0.0, 0.75);
+
+        assert!(renderer.shadow_factor(&light, &shadow_map, shadowed).abs() < EPSILON);
+        assert!((renderer.shadow_factor(&light, &shadow_map, lit) - 1.0).abs() < EPSILON);
+        assert!((renderer.shadow_factor(&light, &shadow_map, above) - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_pcf_shadow_edge() {
+        let shadow_map = shadow_map();
+        let mut renderer = Renderer::new((SIZE, SIZE));
+        let light = Light::new(Vec3f::new(0.0, 0.0, 1.0));
+        let edge = Vec3f::new(0.0625, 0.0, 0.0);
+
+        assert!((renderer.shadow_factor(&light, &shadow_map, edge) - 1.0).abs() < EPSILON);
+
+        renderer.set_shadow_pcf(3);
+        let factor = renderer.shadow_factor(&light, &shadow_map, edge);
+        assert!(factor > 0.0 && factor < 1.0);
+        assert!((factor - 6.0 / 9.0).abs() < EPSILON);
+
+        // even sizes are rounded up explicitly
+        renderer.set_shadow_pcf(2);
+        assert_eq!(renderer.shadow_pcf(), 3);
+        assert!((renderer.shadow_factor(&light, &shadow_map, edge) - factor).abs() < EPSILON);
+        renderer.set_shadow_pcf(0);
+        assert_eq!(renderer.shadow_pcf(), 1);
+    }
+
+    #[test]
+    fn test_light_without_shadows() {
+        let shadow_map = shadow_map();
+        let renderer = Renderer::new((SIZE, SIZE));
+        let mut light = Light::new(Vec3f::new(0.0, 0.0, 1.0));
+        light.cast_shadows = false;
+
+        let shadowed = Vec3f::new(-0.5, 0.0, 0.0);
+        assert!((renderer.shadow_factor(&light, &shadow_map, shadowed) - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_quad_occluder() {
+        let shadow_map = occluded_shadow_map(vec![vec![0, 1, 2, 3]]);
+        let renderer = Renderer::new((SIZE, SIZE));
+        let light = Light::new(Vec3f::new(0.0, 0.0, 1.0));
+
+        // the quad casts the same shadow as its two triangles
+        for point in [Vec3f::new(-0.5, 0.5, 0.0), Vec3f::new(-0.5, -0.5, 0.0)] {
+            assert!(renderer.shadow_factor(&light, &shadow_map, point).abs() < EPSILON);
+        }
+        let lit = Vec3f::new(0.5, 0.0, 0.0);
+        assert!((renderer.shadow_factor(&light, &shadow_map, lit) - 1.0).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::framebuffer::Rgb;
+    use tinyrs::geometry::VecUV2f;
+    use tinyrs::texture::Texture;
+
+    const RED: Rgb = Rgb { r: 255, g: 0, b: 0 };
+    const GREEN: Rgb = Rgb { r: 0, g: 255, b: 0 };
+    const BLUE: Rgb = Rgb { r: 0, g: 0, b: 255 };
+    const WHITE: Rgb = Rgb { r: 255, g: 255, b: 255 };
+
+    fn texture() -> Texture {
+        Texture::new((2, 2), vec![RED, GREEN, BLUE, WHITE]).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        assert!(Texture::new((2, 2), vec![RED; 3]).is_err());
+        assert!(Texture::new((0, 2), Vec::new()).is_err());
+        // more pixels than fit in a u32
+        assert!(Texture::new((1 << 16, 1 << 16), Vec::new()).is_err());
+
+        let texture = texture();
+        assert_eq!(texture.resolution().width, 2);
+        assert_eq!(texture.pixel(1, 0), Some(GREEN));
+        assert_eq!(texture.pixel(2, 0), None);
+    }
+
+    #[test]
+    fn test_sample_corners() {
+        let texture = texture();
+
+        // v = 0 is the bottom row of the image
+        assert_eq!(texture.sample(VecUV2f::new(0.0, 0.0)), BLUE);
+        assert_eq!(texture.sample(VecUV2f::new(1.0, 0.0)), WHITE);
+        assert_eq!(texture.sample(VecUV2f::new(0.0, 1.0)), RED);
+        assert_eq!(texture.sample(VecUV2f::new(1.0, 1.0)), GREEN);
+        assert_eq!(texture.sample(VecUV2f::new(0.25, 0.75)), RED);
+    }
+
+    #[test]
+    fn test_sample_clamps() {
+        let texture = texture();
+        assert_eq!(texture.sample(VecUV2f::new(-1.0, 2.0)), RED);
+        assert_eq!(texture.sample(VecUV2f::new(3.0, -0.5)), WHITE);
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod test {
+    use tinyrs::geometry::{Mat4x1f, Mat4x4f, Vec3f};
+    use tinyrs::renderer::Renderer;
+    use tinyrs::zbuffer::{MultisampleZBuffer, ZBuffer};
+
+    const EPSILON: f64 = 1e-9_f64;
+
+    #[test]
+    fn test_sample_depth() {
+        let mut zbuffer = ZBuffer::new((4, 4), f64::MIN);
+        for y in 0..4 {
+            for x in 0..4 {
+                zbuffer[(x + 4 * y) as usize] = x as f64 + 10.0 * y as f64;
+            }
+        }
+
+        assert!((zbuffer.sample_depth(1.0, 2.0) - 21.0).abs() < EPSILON);
+        assert!((zbuffer.sample_depth(1.5, 2.0) - 21.5).abs() < EPSILON);
+        assert!((zbuffer.sample_depth(1.25, 2.5) - 26.25).abs() < EPSILON);
+        assert!((zbuffer.sample_depth(7.0, -3.0) - 3.0).abs() < EPSILON);
+
+        // uncovered neighbours fall back to the nearest sample
+        zbuffer[3] = f64::MIN;
+        assert_eq!(zbuffer.sample_depth(2.2, 0.4), 2.0);
+    }
+
+    #[test]
+    fn test_unproject() {
+        let renderer = Renderer::new((64, 48));
+        let view_port = Mat4x4f::viewport(0.0, 0.0, 64.0, 48.0);
+        let projection = Mat4x4f::perspective(1.0, 64.0 / 48.0, 0.5, 50.0);
+
+        let point = Vec3f::new(0.3, -0.7, -4.0);
+        let screen = Vec3f::from(view_port * projection * Mat4x1f::from(point));
+        let unprojected = renderer.unproject(screen, view_port, projection).unwrap();
+
+        assert!((unprojected - point).norm() < EPSILON);
+    }
+
+    #[test]
+    fn test_write_pgm16() {
+        let mut zbuffer = ZBuffer::new((4, 2), f64::MIN);
+        for index in 0..8 {
+            zbuffer[index] = if index % 4 < 2 { 1.0 } else { 3.0 };
+        }
+
+        let mut output = Vec::new();
+        zbuffer.write_pgm16(&mut output, 0.0, 4.0).unwrap();
+
+        let header = b"P5\n4 2\n65535\n";
+        assert_eq!(&output[..header.len()], header);
+        let values: Vec<u16> = output[header.len()..].chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        assert_eq!(values, [16384, 16384, 49151, 49151, 16384, 16384, 49151, 49151]);
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut zbuffer = ZBuffer::new((8, 8), f64::MIN);
+        zbuffer[10] = 1.0;
+        let capacity = zbuffer.capacity();
+
+        zbuffer.resize(4, 2);
+        assert_eq!(zbuffer.data().len(), 8);
+        assert_eq!(zbuffer.capacity(), capacity);
+        assert!(zbuffer.data().iter().all(|&depth| depth == f64::MIN));
+
+        zbuffer[3] = 2.0;
+        zbuffer.resize(8, 8);
+        assert_eq!(zbuffer.capacity(), capacity);
+        assert_eq!(zbuffer.depth(7, 7), Some(f64::MIN));
+        assert!(zbuffer.data().iter().all(|&depth| depth == f64::MIN));
+
+        zbuffer.resize(16, 16);
+        assert_eq!(zbuffer.data().len(), 256);
+        assert!(zbuffer.capacity() >= 256);
+        assert_eq!(zbuffer.resolution().width, 16);
+    }
+
+    #[test]
+    fn test_multisample() {
+        for count in [1, 2, 4, 8] {
+            let mut samples = MultisampleZBuffer::new((4, 3), count, -1.0);
+            let offsets: Vec<(f64, f64)> = samples.sample_offsets().collect();
+            assert_eq!(offsets.len(), count);
+            assert!(offsets.iter().all(|&(x, y)| x.abs() < 0.5 && y.abs() < 0.5));
+
+            samples.pixel_mut(3, 2).unwrap()[count - 1] = 2.0;
+            assert_eq!(samples.depth(3, 2, count - 1), Some(2.0));
+            assert_eq!(samples.depth(2, 2, count - 1), Some(-1.0));
+            assert_eq!(samples.depth(3, 2, count), None);
+            assert!(samples.pixel(4, 0).is_none());
+
+            samples.clear();
+            assert_eq!(samples.depth(3, 2, count - 1), Some(-1.0));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported sample count 3")]
+    fn test_multisample_count() {
+        MultisampleZBuffer::new((4, 4), 3, 0.0);
+    }
+}